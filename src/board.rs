@@ -1,11 +1,25 @@
+use std::fmt;
+
 use crate::pieces::{Square, Piece, Color};
 
+mod fen;
+mod san;
+mod see;
+mod zobrist;
+pub use self::fen::{FenError, INITIAL_FEN};
+pub use self::san::SanError;
+
+#[derive(Clone)]
 pub struct Board {
     board: [[ Square ; 8] ; 8],
     en_pasant: EnPasant,
     white_castle: Castle,
     black_castle: Castle,
     turn: Color,
+    halfmove_clock: u32,
+    fullmove_number: u32,
+    key: u64,
+    history: Vec<Undo>,
 }
 struct BoardIter {
     x: i8,
@@ -29,18 +43,70 @@ pub struct Position {
     x: i8,
     y: i8,
 }
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq)]
 enum EnPasant {
     Enable(Position),
     Disable,
 }
 
-#[derive(Clone, Copy)]
-struct Castle {
+#[derive(Clone, Copy, PartialEq)]
+pub struct Castle {
     long: bool,
     short: bool,
 }
 
+#[derive(Clone, Copy, PartialEq)]
+pub enum MoveFlag {
+    Normal,
+    DoublePush,
+    EnPasant,
+    ShortCastle,
+    LongCastle,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct Move {
+    from: Position,
+    to: Position,
+    promotion: Option<Piece>,
+    flag: MoveFlag,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Outcome {
+    Ongoing,
+    Checkmate(Color),
+    Stalemate,
+    Draw(DrawReason),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum DrawReason {
+    FiftyMoves,
+    SeventyFiveMoves,
+    ThreefoldRepetition,
+    FivefoldRepetition,
+    InsufficientMaterial,
+}
+
+#[derive(Clone, Copy)]
+pub struct NullMove {
+    en_pasant: EnPasant,
+    halfmove_clock: u32,
+    key: u64,
+}
+
+#[derive(Clone, Copy)]
+struct Undo {
+    mv: Move,
+    captured: Option<Piece>,
+    en_pasant: EnPasant,
+    white_castle: Castle,
+    black_castle: Castle,
+    halfmove_clock: u32,
+    key: u64,
+}
+
 impl Direction {
     fn step_x(&self) -> i8 {
         match self {
@@ -71,10 +137,10 @@ impl Direction {
         let is_right_to = initial_position.is_right_to(final_position);
         let is_above = initial_position.is_above(final_position);
         if initial_position.is_same_row(final_position) {
-            if is_right_to {
-                Some(Self::Left)
-            } else {
+            if initial_position.is_left_to(final_position) {
                 Some(Self::Right)
+            } else {
+                Some(Self::Left)
             }
         } else if initial_position.is_same_column(final_position) {
             if is_above {
@@ -124,12 +190,12 @@ impl Iterator for BoardIter {
     type Item = (i8, i8);
 
     fn next(&mut self) -> Option<Self::Item> {
-        let next_x: i8 = self.x as i8 + self.dir.step_x();
-        let next_y: i8 = self.y as i8 + self.dir.step_y();
+        let next_x: i8 = self.x + self.dir.step_x();
+        let next_y: i8 = self.y + self.dir.step_y();
         if Self::is_in_range(next_x, next_y){
             self.x = next_x;
             self.y = next_y;
-            Some((self.x, self.y))
+            Some((self.get_x(), self.get_y()))
         } else {
             None
         }
@@ -140,12 +206,36 @@ impl Position {
     fn new_position(x: i8, y: i8) -> Self {
         assert!( 0 < x && x < 9, "Expected 0 < x < 9, found {}",x);
         assert!( 0 < y && y < 9, "Expected 0 < y < 9, found {}",y);
-        Self {x: x, y: y}
+        Self {x, y}
+    }
+    pub fn try_new(x: i8, y: i8) -> Result<Self, String> {
+        if Self::is_valid_position(x, y) {
+            Ok(Self::new_position(x, y))
+        } else {
+            Err(format!("({}, {}) is not a square!", x, y))
+        }
+    }
+    pub fn from_algebraic(text: &str) -> Result<Self, String> {
+        let mut chars = text.chars();
+        let (file, rank) = match (chars.next(), chars.next(), chars.next()) {
+            (Some(file), Some(rank), None) => (file, rank),
+            _ => return Err(format!("'{}' is not a square!", text)),
+        };
+        let x = (file as i32) - ('a' as i32) + 1;
+        let y = (rank as i32) - ('1' as i32) + 1;
+        if !(1..=8).contains(&x) || !(1..=8).contains(&y) {
+            return Err(format!("'{}' is not a square!", text));
+        }
+        Ok(Self::new_position(x as i8, y as i8))
     }
-    fn get_x(&self) -> i8 {
+    pub fn display(&self) -> String {
+        let file = (b'a' + (self.x - 1) as u8) as char;
+        format!("{}{}", file, self.y)
+    }
+    pub fn get_x(&self) -> i8 {
         self.x
     }
-    fn get_y(&self) -> i8 {
+    pub fn get_y(&self) -> i8 {
         self.y
     }
     fn get_y_board(&self) -> usize {
@@ -192,6 +282,11 @@ impl PartialEq for Position {
         self.x == other.x && self.y == other.y
     }
 }
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.display())
+    }
+}
 
 impl Castle {
     pub fn is_long_castle_enable(&self) -> bool {
@@ -208,20 +303,71 @@ impl Castle {
     }
 }
 
+impl Move {
+    pub fn new(from: Position, to: Position) -> Self {
+        Self::new_with_flag(from, to, MoveFlag::Normal)
+    }
+    pub fn new_with_flag(from: Position, to: Position, flag: MoveFlag) -> Self {
+        Self {from, to, promotion: None, flag}
+    }
+    pub fn new_promotion(from: Position, to: Position, promotion: Piece) -> Self {
+        Self {from, to, promotion: Some(promotion), flag: MoveFlag::Normal}
+    }
+    pub fn get_from(&self) -> Position {
+        self.from
+    }
+    pub fn get_to(&self) -> Position {
+        self.to
+    }
+    pub fn get_promotion(&self) -> Option<Piece> {
+        self.promotion
+    }
+    pub fn get_flag(&self) -> MoveFlag {
+        self.flag
+    }
+    pub fn is_castle(&self) -> bool {
+        self.flag == MoveFlag::ShortCastle || self.flag == MoveFlag::LongCastle
+    }
+    pub fn is_en_pasant(&self) -> bool {
+        self.flag == MoveFlag::EnPasant
+    }
+    pub fn is_double_push(&self) -> bool {
+        self.flag == MoveFlag::DoublePush
+    }
+}
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.from, self.to)?;
+        if let Some(promotion) = self.promotion {
+            write!(f, "{}", promotion.display().to_lowercase())?;
+        }
+        Ok(())
+    }
+}
+
 impl Board {
     pub fn new_board() -> Board {
-        Board {
+        let mut board = Board {
             board : [[ Square::Empty ; 8] ; 8],
             en_pasant: EnPasant::Disable,
             white_castle: Castle {long: true, short: true},
             black_castle: Castle {long: true, short: true},
             turn: Color::White,
-        }
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            key: 0,
+            history: Vec::new(),
+        };
+        board.key = board.compute_key();
+        board
     }
     pub fn place_piece(&mut self, piece: Piece, position: Position){
+        self.remove_piece(position);
         let x = position.get_x_board();
         let y = position.get_y_board();
+        self.key ^= self.en_pasant_key() ^ Self::piece_key(piece, position);
         self.board[y][x] = Square::NonEmpty(piece);
+        self.key ^= self.en_pasant_key();
     }
     pub fn place_piece_at(&mut self, piece: Piece, x: i8, y: i8){
         let pos = Position::new_position(x,y);
@@ -230,7 +376,11 @@ impl Board {
     fn remove_piece(&mut self, position: Position){
         let x = position.get_x_board();
         let y = position.get_y_board();
-        self.board[y][x] = Square::Empty;
+        if let Some(piece) = self.board[y][x].get_piece() {
+            self.key ^= self.en_pasant_key() ^ Self::piece_key(piece, position);
+            self.board[y][x] = Square::Empty;
+            self.key ^= self.en_pasant_key();
+        }
     }
     pub fn display(&self){
         for row in self.board.iter() {
@@ -242,33 +392,7 @@ impl Board {
         }
     }
     pub fn initial_position(&mut self){
-        let mut x:i8 = 1;
-        let mut y:i8 = 8;
-        let initial_fen = String::from("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR");
-        for c in initial_fen.chars() {
-            match c {
-                c if c.is_digit(10) => {
-                    let spaces = c.to_digit(10).unwrap() as i8;
-                    x += spaces;
-                },
-                '/' => {
-                    y -= 1;
-                    x = 1;
-                },
-                _ => {
-                    let piece = 
-                        match Self::piece_from_char(c) {
-                            Ok(p) => p,
-                            Err(error) => panic!("{}", error),
-                        };
-                    self.place_piece_at(piece,x,y);
-                    x += 1;
-                },
-            };
-        };
-    }
-    fn piece_from_char(char_piece: char) -> Result<Piece, String> {
-        Piece::piece_from_char(char_piece)
+        *self = Self::from_fen(INITIAL_FEN).unwrap();
     }
     pub fn is_white_in_check(&self) -> bool {
         self.is_in_check(Color::White)
@@ -276,27 +400,139 @@ impl Board {
     pub fn is_black_in_check(&self) -> bool {
         self.is_in_check(Color::Black)
     }
-    fn is_in_check(&self, player: Color) -> bool {
-        let king = self.find_king(player);
-        return self.is_in_check_horizontaly_or_verticaly(king) ||
-        self.is_in_check_by_pawn(king)   ||
-        self.is_in_check_diagonaly(king) ||
-        self.is_in_check_by_knight(king);
-    }
-    fn is_in_check_by_knight(&self, king_position: Position) -> bool {
-        let king_opposite_color = self.get_piece(king_position).unwrap().get_color().opposite();
-        for position in self.get_possible_moves_of_knight_from(king_position).iter() {
-            match self.get_piece(*position) {
-                Some(piece) => if piece.is_knight_of_color(king_opposite_color) {return true;},
-                None => {},
+    pub fn outcome(&self) -> Outcome {
+        if self.legal_moves().is_empty() {
+            let in_check = match self.turn {
+                Color::White => self.is_white_in_check(),
+                Color::Black => self.is_black_in_check(),
+            };
+            return if in_check {
+                Outcome::Checkmate(self.turn.opposite())
+            } else {
+                Outcome::Stalemate
             };
         }
-        false
+        if self.is_insufficient_material() {
+            Outcome::Draw(DrawReason::InsufficientMaterial)
+        } else if self.repetition_count() >= 5 {
+            Outcome::Draw(DrawReason::FivefoldRepetition)
+        } else if self.halfmove_clock >= 150 {
+            Outcome::Draw(DrawReason::SeventyFiveMoves)
+        } else {
+            Outcome::Ongoing
+        }
+    }
+    pub fn claimable_draw(&self) -> Option<DrawReason> {
+        if self.repetition_count() >= 3 {
+            Some(DrawReason::ThreefoldRepetition)
+        } else if self.halfmove_clock >= 100 {
+            Some(DrawReason::FiftyMoves)
+        } else {
+            None
+        }
+    }
+    pub fn get_played_moves(&self) -> Vec<Move> {
+        self.history.iter().map(|undo| undo.mv).collect()
+    }
+    pub fn get_halfmove_clock(&self) -> u32 {
+        self.halfmove_clock
+    }
+    pub fn get_fullmove_number(&self) -> u32 {
+        self.fullmove_number
+    }
+    pub fn repetition_count(&self) -> usize {
+        let repetitions = self.history.iter()
+            .rev()
+            .take(self.halfmove_clock as usize)
+            .filter(|undo| undo.key == self.key)
+            .count();
+        repetitions + 1
+    }
+    fn can_capture_en_pasant(&self) -> bool {
+        let en_pasant = match self.get_en_pasant() {
+            Some(position) => position,
+            None => return false,
+        };
+        let row = match self.turn {
+            Color::White => en_pasant.get_y() - 1,
+            Color::Black => en_pasant.get_y() + 1,
+        };
+        [en_pasant.get_x() - 1, en_pasant.get_x() + 1].iter().any(|x| {
+            Position::is_valid_position(*x, row) &&
+            self.get_piece(Position::new_position(*x, row)) == Some(Piece::Pawn(self.turn)) &&
+            self.can_pawn_capture_en_pasant(Position::new_position(*x, row), en_pasant)
+        })
+    }
+    pub fn is_insufficient_material(&self) -> bool {
+        let mut minor_pieces = 0;
+        let mut bishop_square_colors: Vec<usize> = Vec::new();
+        for (row_index, row) in self.board.iter().enumerate() {
+            for (col_index, square) in row.iter().enumerate() {
+                match square {
+                    Square::Empty | Square::NonEmpty(Piece::King(_)) => {},
+                    Square::NonEmpty(Piece::Bishop(_)) => {
+                        minor_pieces += 1;
+                        bishop_square_colors.push((row_index + col_index) % 2);
+                    },
+                    Square::NonEmpty(Piece::Knight(_)) => minor_pieces += 1,
+                    _ => return false,
+                }
+            }
+        }
+        if minor_pieces <= 1 {
+            return true;
+        }
+        bishop_square_colors.len() == minor_pieces &&
+        bishop_square_colors.iter().all(|color| *color == bishop_square_colors[0])
+    }
+    pub fn is_turn_in_check(&self) -> bool {
+        self.is_in_check(self.turn)
+    }
+    fn is_in_check(&self, player: Color) -> bool {
+        self.find_king(player).is_some_and(|king| self.is_attacked(king, player))
+    }
+    pub fn attackers(&self, position: Position, color: Color) -> Vec<Position> {
+        let mut attackers = self.pawn_attackers(position, color);
+        attackers.extend(self.knight_attackers(position, color));
+        attackers.extend(self.diagonal_attackers(position, color));
+        attackers.extend(self.straight_attackers(position, color));
+        attackers.extend(self.king_attackers(position, color));
+        attackers
+    }
+    fn is_attacked(&self, position: Position, color: Color) -> bool {
+        let attacker = color.opposite();
+        !self.straight_attackers(position, attacker).is_empty() ||
+        !self.pawn_attackers(position, attacker).is_empty()     ||
+        !self.diagonal_attackers(position, attacker).is_empty() ||
+        !self.knight_attackers(position, attacker).is_empty()   ||
+        !self.king_attackers(position, attacker).is_empty()
+    }
+    fn king_attackers(&self, position: Position, color: Color) -> Vec<Position> {
+        let directions = [
+            Direction::Up, Direction::Down, Direction::Left, Direction::Right,
+            Direction::UpRight, Direction::UpLeft, Direction::DownRight, Direction::DownLeft,
+        ];
+        let mut attackers: Vec<Position> = Vec::new();
+        for direction in directions.iter() {
+            if let Some((col, row)) = BoardIter::new(position, *direction).next() {
+                let square = Position::new_position(col, row);
+                if self.get_piece(square) == Some(Piece::King(color)) {
+                    attackers.push(square);
+                }
+            }
+        }
+        attackers
+    }
+    fn knight_attackers(&self, position: Position, color: Color) -> Vec<Position> {
+        self.get_possible_moves_of_knight_from(position)
+            .into_iter()
+            .filter(|square| self.get_piece(*square).is_some_and(|piece| piece.is_knight_of_color(color)))
+            .collect()
     }
     fn get_possible_moves_of_knight_from(&self, position: Position) -> Vec<Position> {
         let pos_x = position.get_x();
         let pos_y = position.get_y();
-        let moves = vec![
+        let moves = [
             (pos_x+1, pos_y+2),
             (pos_x+2, pos_y+1),
             (pos_x-1, pos_y-2),
@@ -312,96 +548,364 @@ impl Board {
                 possible_moves.push(Position::new_position(*x,*y));
             }
         }
-        return possible_moves;
-    }
-    fn is_in_check_by_pawn(&self, king_position: Position) -> bool {
-        match self.get_piece(king_position) {
-            Some(piece) => 
-                if !piece.is_king() {
-                    panic!("Expected King, found {}", piece.display_full_name());
-                },
-            None => panic!("Expected King, found no piece!"),
-        }
-        let king_color = self.get_piece(king_position).unwrap().get_color();
-        match king_color {
-            Color::White => self.is_white_king_in_check_by_pawn(king_position),
-            Color::Black => self.is_black_king_in_check_by_pawn(king_position),
-        }
-    }
-    fn is_white_king_in_check_by_pawn(&self, king_position:Position) -> bool {
-        self.is_king_in_check_by_pawn_in_direction(king_position, Direction::UpRight) || 
-        self.is_king_in_check_by_pawn_in_direction(king_position, Direction::UpLeft)
-    }
-    fn is_black_king_in_check_by_pawn(&self, king_position:Position) -> bool {
-        self.is_king_in_check_by_pawn_in_direction(king_position, Direction::DownRight) || 
-        self.is_king_in_check_by_pawn_in_direction(king_position, Direction::DownLeft)
-    }
-    fn is_king_in_check_by_pawn_in_direction(&self, king_position: Position, direction: Direction) -> bool {
-        let x_pawn_position = king_position.get_x() + direction.step_x();
-        let y_pawn_position = king_position.get_y() + direction.step_y();
-        if Position::is_valid_position(x_pawn_position, y_pawn_position) {
-            let pawn_position = Position::new_position(x_pawn_position, y_pawn_position);
-            if let Some(piece) = self.get_piece(pawn_position) {
-                let opposite_king_color = self.get_piece(king_position).unwrap().get_color().opposite();
-                return piece.is_pawn_of_color(opposite_king_color);
+        possible_moves
+    }
+    fn pawn_attackers(&self, position: Position, color: Color) -> Vec<Position> {
+        let directions = match color {
+            Color::White => [Direction::DownRight, Direction::DownLeft],
+            Color::Black => [Direction::UpRight, Direction::UpLeft],
+        };
+        let mut attackers: Vec<Position> = Vec::new();
+        for direction in directions.iter() {
+            if let Some((col, row)) = BoardIter::new(position, *direction).next() {
+                let square = Position::new_position(col, row);
+                if self.get_piece(square).is_some_and(|piece| piece.is_pawn_of_color(color)) {
+                    attackers.push(square);
+                }
             }
         }
-        false
+        attackers
     }
-    fn is_in_check_diagonaly(&self, king_position: Position) -> bool {
-        let king_color = self.get_piece(king_position).unwrap().get_color();
+    fn diagonal_attackers(&self, position: Position, color: Color) -> Vec<Position> {
         let directions = [Direction::UpRight, Direction::UpLeft, Direction::DownRight, Direction::DownLeft];
+        let mut attackers: Vec<Position> = Vec::new();
         for direction in directions.iter() {
-            let iterator = BoardIter::new(king_position, *direction);
-            for (col, row) in iterator {
-                let position = Position::new_position(col, row);
-                if let Some(piece) = self.get_piece(position) {
-                    if piece.is_queen_or_bishop_of_color(king_color.opposite()){
-                        return true;
-                    } else {
-                        break;
+            for (col, row) in BoardIter::new(position, *direction) {
+                let square = Position::new_position(col, row);
+                if let Some(piece) = self.get_piece(square) {
+                    if piece.is_queen_or_bishop_of_color(color) {
+                        attackers.push(square);
                     }
+                    break;
                 }
             }
         }
-        false
+        attackers
     }
-    fn is_in_check_horizontaly_or_verticaly(&self, king_position: Position) -> bool {
-        let king_color = self.get_piece(king_position).unwrap().get_color();
+    fn straight_attackers(&self, position: Position, color: Color) -> Vec<Position> {
         let directions = [Direction::Up, Direction::Down, Direction::Left, Direction::Right];
+        let mut attackers: Vec<Position> = Vec::new();
         for direction in directions.iter() {
-            let iterator = BoardIter::new(king_position, *direction);
-            for (col, row) in iterator {
-                let position = Position::new_position(col, row);
-                if let Some(piece) = self.get_piece(position) {
-                    if piece.is_queen_or_rook_of_color(king_color.opposite()){
-                        return true;
-                    } else {
-                        break;
+            for (col, row) in BoardIter::new(position, *direction) {
+                let square = Position::new_position(col, row);
+                if let Some(piece) = self.get_piece(square) {
+                    if piece.is_queen_or_rook_of_color(color) {
+                        attackers.push(square);
                     }
+                    break;
                 }
             }
         }
-        false
+        attackers
     }
-    fn find_king(&self, color: Color) -> Position {
+    fn find_king(&self, color: Color) -> Option<Position> {
         for (row_index, row) in self.board.iter().enumerate() {
             for (col_index, square) in row.iter().enumerate() {
                 if let Square::NonEmpty(piece) = square {
                     if piece.is_king() && piece.get_color() == color {
-                        return Position::new_position((col_index+1) as i8, (8-row_index) as i8);
+                        return Some(Position::new_position((col_index+1) as i8, (8-row_index) as i8));
                     }
                 };
             }
         }
-        panic!("No kings on the board!");
+        None
     }
     pub fn get_en_pasant(&self) -> Option<Position> {
         match self.en_pasant {
-            EnPasant::Enable(position) => Some(position.clone()),
+            EnPasant::Enable(position) => Some(position),
             EnPasant::Disable => None,
         }
     }
+    pub fn get_turn(&self) -> Color {
+        self.turn
+    }
+    pub fn get_castle(&self, color: Color) -> Castle {
+        match color {
+            Color::White => self.white_castle,
+            Color::Black => self.black_castle,
+        }
+    }
+    pub fn set_en_pasant(&mut self, position: Option<Position>) {
+        self.key ^= self.en_pasant_key();
+        self.en_pasant = match position {
+            Some(position) => EnPasant::Enable(position),
+            None => EnPasant::Disable,
+        };
+        self.key ^= self.en_pasant_key();
+    }
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let mut board = Board {history: Vec::new(), ..*self};
+        self.pseudo_legal_moves()
+            .into_iter()
+            .filter(|mv| !board.leaves_king_in_check(*mv))
+            .collect()
+    }
+    fn pseudo_legal_moves(&self) -> Vec<Move> {
+        let mut moves: Vec<Move> = Vec::new();
+        for x in 1..9 {
+            for y in 1..9 {
+                let position = Position::new_position(x, y);
+                match self.get_piece(position) {
+                    Some(piece) if piece.get_color() == self.turn => {},
+                    _ => continue,
+                };
+                for destination in self.get_candidate_destinations(position) {
+                    if !self.can_move(position, destination) {
+                        continue;
+                    }
+                    if self.is_promotion(position, destination) {
+                        for piece in Self::promotion_pieces(self.turn) {
+                            moves.push(Move::new_promotion(position, destination, piece));
+                        }
+                    } else {
+                        moves.push(self.new_move(position, destination));
+                    }
+                }
+            }
+        }
+        moves
+    }
+    fn get_candidate_destinations(&self, position: Position) -> Vec<Position> {
+        let piece = self.get_piece(position).unwrap();
+        let mut destinations: Vec<Position> = Vec::new();
+        let directions = match piece {
+            Piece::Knight(_) => return self.get_possible_moves_of_knight_from(position),
+            Piece::Rook(_) => vec![Direction::Up, Direction::Down, Direction::Left, Direction::Right],
+            Piece::Bishop(_) => vec![Direction::UpRight, Direction::UpLeft, Direction::DownRight, Direction::DownLeft],
+            Piece::Queen(_) | Piece::King(_) | Piece::Pawn(_) => vec![
+                Direction::Up, Direction::Down, Direction::Left, Direction::Right,
+                Direction::UpRight, Direction::UpLeft, Direction::DownRight, Direction::DownLeft,
+            ],
+        };
+        let max_steps = match piece {
+            Piece::King(_) => {
+                let row = position.get_y();
+                if position.get_x() == 5 && (row == 1 || row == 8) {
+                    destinations.push(Position::new_position(3, row));
+                    destinations.push(Position::new_position(7, row));
+                }
+                1
+            },
+            Piece::Pawn(_) => 2,
+            _ => 8,
+        };
+        for direction in directions {
+            for (col, row) in BoardIter::new(position, direction).take(max_steps) {
+                let destination = Position::new_position(col, row);
+                destinations.push(destination);
+                if self.get_piece(destination).is_some() {
+                    break;
+                }
+            }
+        }
+        destinations
+    }
+    pub fn mobility(&self, position: Position) -> usize {
+        let piece = match self.get_piece(position) {
+            Some(Piece::King(_)) | Some(Piece::Pawn(_)) | None => return 0,
+            Some(piece) => piece,
+        };
+        self.get_candidate_destinations(position)
+            .into_iter()
+            .filter(|destination| self.get_piece(*destination).is_none_or(|other| other.get_color() != piece.get_color()))
+            .count()
+    }
+    pub fn is_capture(&self, mv: Move) -> bool {
+        mv.is_en_pasant() || self.get_piece(mv.get_to()).is_some()
+    }
+    fn is_promotion(&self, from: Position, to: Position) -> bool {
+        match self.get_piece(from) {
+            Some(Piece::Pawn(_)) => to.get_y() == 1 || to.get_y() == 8,
+            _ => false,
+        }
+    }
+    fn promotion_pieces(color: Color) -> [Piece; 4] {
+        [Piece::Queen(color), Piece::Rook(color), Piece::Bishop(color), Piece::Knight(color)]
+    }
+    pub fn parse_move(&self, text: &str) -> Result<Move, String> {
+        if !text.is_ascii() || (text.len() != 4 && text.len() != 5) {
+            return Err(format!("'{}' is not a valid move!", text));
+        }
+        let from = Position::from_algebraic(&text[0..2])?;
+        let to = Position::from_algebraic(&text[2..4])?;
+        let promotion = match text[4..].chars().next() {
+            Some(c) => match c.to_ascii_lowercase() {
+                'q' => Some(Piece::Queen(self.turn)),
+                'r' => Some(Piece::Rook(self.turn)),
+                'b' => Some(Piece::Bishop(self.turn)),
+                'n' => Some(Piece::Knight(self.turn)),
+                _ => return Err(format!("'{}' is not a promotion piece!", c)),
+            },
+            None => None,
+        };
+        self.legal_moves()
+            .into_iter()
+            .find(|mv| mv.get_from() == from && mv.get_to() == to && mv.get_promotion() == promotion)
+            .ok_or(format!("'{}' is not a legal move!", text))
+    }
+    fn new_move(&self, from: Position, to: Position) -> Move {
+        let piece = self.get_piece(from).unwrap();
+        let flag = match (piece, from.distances(to)) {
+            (Piece::Pawn(_), [0, 2]) => MoveFlag::DoublePush,
+            (Piece::Pawn(_), [1, 1]) if self.get_piece(to).is_none() => MoveFlag::EnPasant,
+            (Piece::King(_), [2, 0]) if to.get_x() == 7 => MoveFlag::ShortCastle,
+            (Piece::King(_), [2, 0]) => MoveFlag::LongCastle,
+            _ => MoveFlag::Normal,
+        };
+        Move::new_with_flag(from, to, flag)
+    }
+    fn leaves_king_in_check(&mut self, mv: Move) -> bool {
+        let player = self.turn;
+        self.make_move(mv);
+        let in_check = self.is_in_check(player);
+        self.unmake_move();
+        in_check
+    }
+    pub fn try_make_move(&mut self, mv: Move) -> Result<(), String> {
+        if !self.legal_moves().contains(&mv) {
+            return Err(format!("'{}' is not a legal move!", mv));
+        }
+        self.make_move(mv);
+        Ok(())
+    }
+    pub(crate) fn make_move(&mut self, mv: Move) {
+        let from = mv.get_from();
+        let to = mv.get_to();
+        let piece = self.get_piece(from).unwrap();
+        let captured_position =
+            if mv.is_en_pasant() {
+                Position::new_position(to.get_x(), from.get_y())
+            } else {
+                to
+        };
+        let captured = self.get_piece(captured_position);
+        self.history.push(Undo {
+            mv,
+            captured,
+            en_pasant: self.en_pasant,
+            white_castle: self.white_castle,
+            black_castle: self.black_castle,
+            halfmove_clock: self.halfmove_clock,
+            key: self.key,
+        });
+
+        self.remove_piece(captured_position);
+        self.remove_piece(from);
+        self.place_piece(mv.get_promotion().unwrap_or(piece), to);
+        if let Some((rook_from, rook_to)) = Self::castle_rook_squares(mv) {
+            let rook = self.get_piece(rook_from).unwrap();
+            self.remove_piece(rook_from);
+            self.place_piece(rook, rook_to);
+        }
+
+        self.key ^= self.castle_key() ^ self.en_pasant_key() ^ self.turn_key();
+        self.update_castle_rights(from);
+        self.update_castle_rights(to);
+        self.en_pasant =
+            if mv.is_double_push() {
+                EnPasant::Enable(Position::new_position(from.get_x(), (from.get_y() + to.get_y()) / 2))
+            } else {
+                EnPasant::Disable
+        };
+        self.halfmove_clock =
+            if piece.is_pawn_of_color(self.turn) || captured.is_some() {
+                0
+            } else {
+                self.halfmove_clock + 1
+        };
+        if self.turn == Color::Black {
+            self.fullmove_number += 1;
+        }
+        self.turn = self.turn.opposite();
+        self.key ^= self.castle_key() ^ self.en_pasant_key() ^ self.turn_key();
+    }
+    pub fn unmake_move(&mut self) -> Option<Move> {
+        let undo = self.history.pop()?;
+        let mv = undo.mv;
+        let from = mv.get_from();
+        let to = mv.get_to();
+        let piece = self.get_piece(to).unwrap();
+        let piece =
+            if mv.get_promotion().is_some() {
+                Piece::Pawn(piece.get_color())
+            } else {
+                piece
+        };
+
+        if let Some((rook_from, rook_to)) = Self::castle_rook_squares(mv) {
+            let rook = self.get_piece(rook_to).unwrap();
+            self.remove_piece(rook_to);
+            self.place_piece(rook, rook_from);
+        }
+        self.remove_piece(to);
+        self.place_piece(piece, from);
+        if let Some(captured) = undo.captured {
+            let captured_position =
+                if mv.is_en_pasant() {
+                    Position::new_position(to.get_x(), from.get_y())
+                } else {
+                    to
+            };
+            self.place_piece(captured, captured_position);
+        }
+
+        self.en_pasant = undo.en_pasant;
+        self.white_castle = undo.white_castle;
+        self.black_castle = undo.black_castle;
+        self.halfmove_clock = undo.halfmove_clock;
+        self.key = undo.key;
+        self.turn = self.turn.opposite();
+        if self.turn == Color::Black {
+            self.fullmove_number -= 1;
+        }
+        Some(mv)
+    }
+    pub fn make_null_move(&mut self) -> NullMove {
+        let undo = NullMove {en_pasant: self.en_pasant, halfmove_clock: self.halfmove_clock, key: self.key};
+        self.key ^= self.en_pasant_key() ^ self.turn_key();
+        self.en_pasant = EnPasant::Disable;
+        self.halfmove_clock = 0;
+        self.turn = self.turn.opposite();
+        self.key ^= self.turn_key();
+        undo
+    }
+    pub fn unmake_null_move(&mut self, undo: NullMove) {
+        self.en_pasant = undo.en_pasant;
+        self.halfmove_clock = undo.halfmove_clock;
+        self.key = undo.key;
+        self.turn = self.turn.opposite();
+    }
+    pub fn has_non_pawn_material(&self, color: Color) -> bool {
+        self.board.iter().flatten().any(|square| match square.get_piece() {
+            Some(Piece::Pawn(_)) | Some(Piece::King(_)) | None => false,
+            Some(piece) => piece.get_color() == color,
+        })
+    }
+    fn castle_rook_squares(mv: Move) -> Option<(Position, Position)> {
+        let row = mv.get_from().get_y();
+        match mv.get_flag() {
+            MoveFlag::ShortCastle => Some((Position::new_position(8, row), Position::new_position(6, row))),
+            MoveFlag::LongCastle => Some((Position::new_position(1, row), Position::new_position(4, row))),
+            _ => None,
+        }
+    }
+    fn update_castle_rights(&mut self, position: Position) {
+        let (x, y) = (position.get_x(), position.get_y());
+        let castle = match y {
+            1 => &mut self.white_castle,
+            8 => &mut self.black_castle,
+            _ => return,
+        };
+        match x {
+            1 => castle.disable_long_castle(),
+            5 => {
+                castle.disable_long_castle();
+                castle.disable_short_castle();
+            },
+            8 => castle.disable_short_castle(),
+            _ => {},
+        }
+    }
     pub fn can_move(&self, initial_pos: Position, final_pos: Position) -> bool {
         if initial_pos == final_pos {
             return false;
@@ -411,6 +915,11 @@ impl Board {
                 Some(p) => p,
                 None => return false,
         };
+        if let Some(other_piece) = self.get_piece(final_pos) {
+            if piece.are_same_color(&other_piece) {
+                return false;
+            }
+        }
         match piece {
             Piece::Queen(_)  => self.can_queen_move(initial_pos, final_pos),
             Piece::Rook(_)   => self.can_rook_move(initial_pos, final_pos),
@@ -421,26 +930,108 @@ impl Board {
         }
     }
     fn can_queen_move(&self, initial_pos: Position, final_pos: Position) -> bool {
-        initial_pos.is_same_row(final_pos) || initial_pos.is_same_column(final_pos) || initial_pos.is_same_diagonal(final_pos)
+        self.can_rook_move(initial_pos, final_pos) || self.can_bishop_move(initial_pos, final_pos)
     }
     fn can_rook_move(&self, initial_pos: Position, final_pos: Position) -> bool {
-        initial_pos.is_same_row(final_pos) || initial_pos.is_same_column(final_pos)
+        (initial_pos.is_same_row(final_pos) || initial_pos.is_same_column(final_pos)) &&
+        self.is_path_clear(initial_pos, final_pos)
     }
     fn can_bishop_move(&self, initial_pos: Position, final_pos: Position) -> bool {
-        initial_pos.is_same_diagonal(final_pos)
+        initial_pos.is_same_diagonal(final_pos) && self.is_path_clear(initial_pos, final_pos)
     }
     fn can_king_move(&self, initial_pos: Position, final_pos: Position) -> bool {
         let distances = initial_pos.distances(final_pos);
-        distances[0] + distances[1] <= 2 && distances[0] < 2 && distances[1] < 2
+        distances[0] + distances[1] <= 2 && distances[0] < 2 && distances[1] < 2 ||
+        self.can_castle(initial_pos, final_pos)
+    }
+    fn can_castle(&self, initial_pos: Position, final_pos: Position) -> bool {
+        let color = self.get_piece(initial_pos).unwrap().get_color();
+        let (row, castle) = match color {
+            Color::White => (1, self.white_castle),
+            Color::Black => (8, self.black_castle),
+        };
+        if initial_pos != Position::new_position(5, row) || !initial_pos.is_same_row(final_pos) {
+            return false;
+        }
+        let (rook_x, passing_x, is_enable) = match final_pos.get_x() {
+            7 => (8, 6, castle.is_short_castle_enable()),
+            3 => (1, 4, castle.is_long_castle_enable()),
+            _ => return false,
+        };
+        let rook_position = Position::new_position(rook_x, row);
+        let has_rook = match self.get_piece(rook_position) {
+            Some(Piece::Rook(rook_color)) => rook_color == color,
+            _ => false,
+        };
+        is_enable && has_rook &&
+        self.is_path_clear(initial_pos, rook_position) &&
+        !self.is_attacked(initial_pos, color) &&
+        !self.is_attacked(Position::new_position(passing_x, row), color) &&
+        !self.is_attacked(final_pos, color)
     }
     fn can_knight_move(&self, initial_pos: Position, final_pos: Position) -> bool {
         let distances = initial_pos.distances(final_pos);
         distances[0] == 2 && distances[1] == 1 || distances[0] == 1 && distances[1] == 2
     }
     fn can_pawn_move(&self,  initial_pos: Position, final_pos: Position) -> bool {
-        unimplemented!()
+        let pawn = self.get_piece(initial_pos).unwrap();
+        let color = pawn.get_color();
+        let is_forward = match color {
+            Color::White => initial_pos.is_below(final_pos),
+            Color::Black => initial_pos.is_above(final_pos),
+        };
+        if !is_forward {
+            return false;
+        }
+        match initial_pos.distances(final_pos) {
+            [0, 1] => self.get_piece(final_pos).is_none(),
+            [0, 2] => {
+                initial_pos.get_y() == Self::pawn_initial_row(color) &&
+                self.is_path_clear(initial_pos, final_pos) &&
+                self.get_piece(final_pos).is_none()
+            },
+            [1, 1] => match self.get_piece(final_pos) {
+                Some(piece) => !piece.are_same_color(&pawn),
+                None => self.can_pawn_capture_en_pasant(initial_pos, final_pos),
+            },
+            _ => false,
+        }
+    }
+    fn can_pawn_capture_en_pasant(&self, initial_pos: Position, final_pos: Position) -> bool {
+        if self.get_en_pasant() != Some(final_pos) {
+            return false;
+        }
+        let pawn_color = self.get_piece(initial_pos).unwrap().get_color();
+        let captured_position = Position::new_position(final_pos.get_x(), initial_pos.get_y());
+        match self.get_piece(captured_position) {
+            Some(piece) => piece.is_pawn_of_color(pawn_color.opposite()),
+            None => false,
+        }
+    }
+    fn pawn_initial_row(color: Color) -> i8 {
+        match color {
+            Color::White => 2,
+            Color::Black => 7,
+        }
+    }
+    fn is_path_clear(&self, initial_pos: Position, final_pos: Position) -> bool {
+        let direction =
+            match Direction::get_direction_from_to(initial_pos, final_pos) {
+                Some(d) => d,
+                None => return false,
+        };
+        for (col, row) in BoardIter::new(initial_pos, direction) {
+            let position = Position::new_position(col, row);
+            if position == final_pos {
+                return true;
+            }
+            if self.get_piece(position).is_some() {
+                return false;
+            }
+        }
+        false
     }
-    fn get_piece(&self, position: Position) -> Option<Piece> {
+    pub fn get_piece(&self, position: Position) -> Option<Piece> {
         let x = position.get_x_board();
         let y = position.get_y_board();
         self.board[y][x].get_piece()
@@ -459,6 +1050,487 @@ mod tests {
         assert!(!my_board.is_white_in_check());
         assert!(!my_board.is_black_in_check());
     }
-}
 
+    #[test]
+    fn algebraic_positions(){
+        let e4 = Position::from_algebraic("e4").unwrap();
+        assert!(e4 == Position::new_position(5, 4));
+        assert_eq!(e4.to_string(), "e4");
+        assert_eq!(Position::from_algebraic("a1").unwrap().to_string(), "a1");
+        assert_eq!(Position::from_algebraic("h8").unwrap().to_string(), "h8");
+        assert!(Position::from_algebraic("i1").is_err());
+        assert!(Position::from_algebraic("a0").is_err());
+        assert!(Position::from_algebraic("a10").is_err());
+        assert!(Position::from_algebraic("").is_err());
+        assert!(Position::try_new(8, 8).unwrap() == Position::from_algebraic("h8").unwrap());
+        assert!(Position::try_new(0, 4).is_err());
+        assert!(Position::try_new(4, 9).is_err());
+    }
+
+    #[test]
+    fn pawn_pushes(){
+        let mut my_board = Board::new_board();
+        my_board.initial_position();
+        let e2 = Position::new_position(5, 2);
+        assert!(my_board.can_move(e2, Position::new_position(5, 3)));
+        assert!(my_board.can_move(e2, Position::new_position(5, 4)));
+        assert!(!my_board.can_move(e2, Position::new_position(5, 5)));
+        assert!(!my_board.can_move(e2, Position::new_position(5, 1)));
+        let e7 = Position::new_position(5, 7);
+        assert!(my_board.can_move(e7, Position::new_position(5, 6)));
+        assert!(my_board.can_move(e7, Position::new_position(5, 5)));
+        assert!(!my_board.can_move(e7, Position::new_position(5, 8)));
+
+        my_board.place_piece_at(Piece::Knight(Color::Black), 5, 3);
+        assert!(!my_board.can_move(e2, Position::new_position(5, 3)));
+        assert!(!my_board.can_move(e2, Position::new_position(5, 4)));
+
+        let e3 = Position::new_position(5, 3);
+        my_board.remove_piece(e3);
+        my_board.place_piece_at(Piece::Pawn(Color::White), 5, 3);
+        assert!(!my_board.can_move(e3, Position::new_position(5, 5)));
+    }
+
+    #[test]
+    fn pawn_captures(){
+        let mut my_board = Board::new_board();
+        my_board.place_piece_at(Piece::Pawn(Color::White), 4, 4);
+        my_board.place_piece_at(Piece::Pawn(Color::Black), 5, 5);
+        my_board.place_piece_at(Piece::Pawn(Color::White), 3, 5);
+        let d4 = Position::new_position(4, 4);
+        assert!(my_board.can_move(d4, Position::new_position(5, 5)));
+        assert!(!my_board.can_move(d4, Position::new_position(3, 5)));
+        assert!(!my_board.can_move(d4, Position::new_position(3, 3)));
+        let e5 = Position::new_position(5, 5);
+        assert!(my_board.can_move(e5, d4));
+        assert!(!my_board.can_move(e5, Position::new_position(6, 6)));
+    }
+
+    #[test]
+    fn pawn_captures_en_pasant(){
+        let mut my_board = Board::new_board();
+        my_board.place_piece_at(Piece::Pawn(Color::White), 5, 5);
+        my_board.place_piece_at(Piece::Pawn(Color::Black), 4, 5);
+        let e5 = Position::new_position(5, 5);
+        let d6 = Position::new_position(4, 6);
+        assert!(!my_board.can_move(e5, d6));
+        my_board.set_en_pasant(Some(d6));
+        assert!(my_board.can_move(e5, d6));
+        assert!(!my_board.can_move(e5, Position::new_position(6, 6)));
+
+        let mut my_board = Board::new_board();
+        my_board.place_piece_at(Piece::Pawn(Color::Black), 3, 4);
+        my_board.place_piece_at(Piece::Pawn(Color::White), 2, 4);
+        let c4 = Position::new_position(3, 4);
+        let b3 = Position::new_position(2, 3);
+        my_board.set_en_pasant(Some(b3));
+        assert!(my_board.can_move(c4, b3));
+    }
+
+    #[test]
+    fn sliding_pieces_are_blocked(){
+        let mut my_board = Board::new_board();
+        my_board.initial_position();
+        let a1 = Position::new_position(1, 1);
+        let c1 = Position::new_position(3, 1);
+        let d1 = Position::new_position(4, 1);
+        assert!(!my_board.can_move(a1, Position::new_position(1, 8)));
+        assert!(!my_board.can_move(a1, Position::new_position(1, 3)));
+        assert!(!my_board.can_move(c1, Position::new_position(6, 4)));
+        assert!(!my_board.can_move(d1, Position::new_position(4, 4)));
+        assert!(!my_board.can_move(d1, Position::new_position(8, 5)));
+
+        my_board.remove_piece(Position::new_position(1, 2));
+        assert!(my_board.can_move(a1, Position::new_position(1, 3)));
+        assert!(my_board.can_move(a1, Position::new_position(1, 7)));
+        assert!(!my_board.can_move(a1, Position::new_position(1, 8)));
+
+        my_board.remove_piece(Position::new_position(5, 2));
+        assert!(my_board.can_move(d1, Position::new_position(8, 5)));
+        assert!(!my_board.can_move(d1, Position::new_position(4, 4)));
+    }
+
+    #[test]
+    fn cannot_capture_own_pieces(){
+        let mut my_board = Board::new_board();
+        my_board.initial_position();
+        let e1 = Position::new_position(5, 1);
+        let g1 = Position::new_position(7, 1);
+        assert!(!my_board.can_move(e1, Position::new_position(5, 2)));
+        assert!(!my_board.can_move(e1, Position::new_position(6, 1)));
+        assert!(!my_board.can_move(g1, Position::new_position(5, 2)));
+        assert!(my_board.can_move(g1, Position::new_position(6, 3)));
+    }
+
+    #[test]
+    fn initial_position_legal_moves(){
+        let mut my_board = Board::new_board();
+        my_board.initial_position();
+        assert_eq!(my_board.legal_moves().len(), 20);
+        my_board.turn = Color::Black;
+        assert_eq!(my_board.legal_moves().len(), 20);
+    }
+
+    #[test]
+    fn legal_moves_keep_king_out_of_check(){
+        let mut my_board = Board::new_board();
+        my_board.place_piece_at(Piece::King(Color::White), 5, 1);
+        my_board.place_piece_at(Piece::Bishop(Color::White), 5, 2);
+        my_board.place_piece_at(Piece::Rook(Color::Black), 5, 8);
+        my_board.place_piece_at(Piece::King(Color::Black), 1, 8);
+        let e2 = Position::new_position(5, 2);
+        let moves = my_board.legal_moves();
+        assert!(moves.iter().all(|mv| mv.get_from() != e2));
+        assert_eq!(moves.len(), 4);
+
+        my_board.remove_piece(e2);
+        let moves = my_board.legal_moves();
+        assert!(moves.iter().all(|mv| mv.get_to().get_x() != 5));
+        assert_eq!(moves.len(), 4);
+    }
+
+    fn perft(board: &mut Board, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        let mut nodes = 0;
+        for mv in board.legal_moves() {
+            board.make_move(mv);
+            nodes += perft(board, depth - 1);
+            board.unmake_move();
+        }
+        nodes
+    }
+
+    #[test]
+    fn perft_initial_position(){
+        let mut my_board = Board::new_board();
+        my_board.initial_position();
+        assert_eq!(perft(&mut my_board, 1), 20);
+        assert_eq!(perft(&mut my_board, 2), 400);
+        assert_eq!(perft(&mut my_board, 3), 8902);
+    }
+
+    #[test]
+    fn perft_tricky_positions(){
+        let mut my_board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+        assert_eq!(perft(&mut my_board, 1), 48);
+        assert_eq!(perft(&mut my_board, 2), 2039);
+        let mut my_board = Board::from_fen("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1").unwrap();
+        assert_eq!(perft(&mut my_board, 3), 2812);
+        let mut my_board = Board::from_fen("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1").unwrap();
+        assert_eq!(perft(&mut my_board, 2), 264);
+        let mut my_board = Board::from_fen("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8").unwrap();
+        assert_eq!(perft(&mut my_board, 2), 1486);
+    }
+
+    #[test]
+    fn make_and_unmake_move(){
+        let mut my_board = Board::new_board();
+        my_board.initial_position();
+        let initial = my_board.clone();
+        let e2 = Position::new_position(5, 2);
+        let e4 = Position::new_position(5, 4);
+        let double_push = my_board.legal_moves().into_iter().find(|mv| mv.get_from() == e2 && mv.get_to() == e4).unwrap();
+        assert!(double_push.is_double_push());
+        my_board.make_move(double_push);
+        assert!(my_board.get_piece(e2).is_none());
+        assert!(my_board.get_piece(e4) == Some(Piece::Pawn(Color::White)));
+        assert!(my_board.get_en_pasant() == Some(Position::new_position(5, 3)));
+        assert!(my_board.get_turn() == Color::Black);
+
+        assert!(my_board.unmake_move() == Some(double_push));
+        assert!(my_board.board == initial.board);
+        assert!(my_board.en_pasant == initial.en_pasant);
+        assert!(my_board.get_turn() == Color::White);
+        assert!(my_board.unmake_move().is_none());
+    }
+
+    #[test]
+    fn make_and_unmake_en_pasant(){
+        let mut my_board = Board::new_board();
+        my_board.place_piece_at(Piece::King(Color::White), 5, 1);
+        my_board.place_piece_at(Piece::King(Color::Black), 5, 8);
+        my_board.place_piece_at(Piece::Pawn(Color::White), 5, 5);
+        my_board.place_piece_at(Piece::Pawn(Color::Black), 4, 7);
+        my_board.turn = Color::Black;
+        my_board.make_move(Move::new_with_flag(Position::new_position(4, 7), Position::new_position(4, 5), MoveFlag::DoublePush));
+        let before = my_board.clone();
+        let capture = my_board.legal_moves().into_iter().find(|mv| mv.is_en_pasant()).unwrap();
+        my_board.make_move(capture);
+        assert!(my_board.get_piece(Position::new_position(4, 5)).is_none());
+        assert!(my_board.get_piece(Position::new_position(4, 6)) == Some(Piece::Pawn(Color::White)));
+        my_board.unmake_move();
+        assert!(my_board.board == before.board);
+        assert!(my_board.en_pasant == before.en_pasant);
+    }
+
+    #[test]
+    fn try_make_move_rejects_illegal_moves(){
+        let mut my_board = Board::from_fen(INITIAL_FEN).unwrap();
+        let empty = Move::new(Position::new_position(5, 4), Position::new_position(5, 5));
+        assert_eq!(my_board.try_make_move(empty), Err(String::from("'e4e5' is not a legal move!")));
+        let blocked = Move::new(Position::new_position(1, 1), Position::new_position(1, 3));
+        assert!(my_board.try_make_move(blocked).is_err());
+        assert!(my_board.to_fen() == INITIAL_FEN);
+        assert!(my_board.unmake_move().is_none());
+
+        let double_push = my_board.parse_move("e2e4").unwrap();
+        assert_eq!(my_board.try_make_move(double_push), Ok(()));
+        assert!(my_board.unmake_move() == Some(double_push));
+    }
+
+    #[test]
+    fn moving_king_or_rook_disables_castle(){
+        let mut my_board = Board::new_board();
+        my_board.place_piece_at(Piece::King(Color::White), 5, 1);
+        my_board.place_piece_at(Piece::Rook(Color::White), 1, 1);
+        my_board.place_piece_at(Piece::Rook(Color::White), 8, 1);
+        my_board.place_piece_at(Piece::King(Color::Black), 5, 8);
+        my_board.place_piece_at(Piece::Rook(Color::Black), 8, 8);
+        my_board.make_move(Move::new(Position::new_position(8, 1), Position::new_position(8, 8)));
+        assert!(my_board.white_castle.is_long_castle_enable());
+        assert!(!my_board.white_castle.is_short_castle_enable());
+        assert!(my_board.black_castle.is_long_castle_enable());
+        assert!(!my_board.black_castle.is_short_castle_enable());
+        my_board.make_move(Move::new(Position::new_position(5, 8), Position::new_position(4, 8)));
+        assert!(!my_board.black_castle.is_long_castle_enable());
+        my_board.unmake_move();
+        my_board.unmake_move();
+        assert!(my_board.white_castle.is_short_castle_enable());
+        assert!(my_board.black_castle.is_short_castle_enable());
+        assert!(my_board.black_castle.is_long_castle_enable());
+    }
+
+    fn castle_position() -> Board {
+        let mut my_board = Board::new_board();
+        my_board.place_piece_at(Piece::King(Color::White), 5, 1);
+        my_board.place_piece_at(Piece::Rook(Color::White), 1, 1);
+        my_board.place_piece_at(Piece::Rook(Color::White), 8, 1);
+        my_board.place_piece_at(Piece::King(Color::Black), 5, 8);
+        my_board.place_piece_at(Piece::Rook(Color::Black), 1, 8);
+        my_board.place_piece_at(Piece::Rook(Color::Black), 8, 8);
+        my_board
+    }
+
+    #[test]
+    fn castle_moves(){
+        let mut my_board = castle_position();
+        let e1 = Position::new_position(5, 1);
+        let castles: Vec<Move> = my_board.legal_moves().into_iter().filter(|mv| mv.is_castle()).collect();
+        assert_eq!(castles.len(), 2);
+        assert!(my_board.can_move(e1, Position::new_position(7, 1)));
+        assert!(my_board.can_move(e1, Position::new_position(3, 1)));
+
+        let short_castle = castles.into_iter().find(|mv| mv.get_flag() == MoveFlag::ShortCastle).unwrap();
+        my_board.make_move(short_castle);
+        assert!(my_board.get_piece(Position::new_position(7, 1)) == Some(Piece::King(Color::White)));
+        assert!(my_board.get_piece(Position::new_position(6, 1)) == Some(Piece::Rook(Color::White)));
+        assert!(my_board.get_piece(Position::new_position(8, 1)).is_none());
+        assert!(!my_board.white_castle.is_short_castle_enable());
+        assert!(!my_board.white_castle.is_long_castle_enable());
+        my_board.unmake_move();
+        assert!(my_board.get_piece(e1) == Some(Piece::King(Color::White)));
+        assert!(my_board.get_piece(Position::new_position(8, 1)) == Some(Piece::Rook(Color::White)));
+        assert!(my_board.white_castle.is_short_castle_enable());
+    }
+
+    #[test]
+    fn cannot_castle_through_check_or_pieces(){
+        let mut my_board = castle_position();
+        let e1 = Position::new_position(5, 1);
+        my_board.place_piece_at(Piece::Knight(Color::White), 2, 1);
+        assert!(!my_board.can_move(e1, Position::new_position(3, 1)));
+        assert!(my_board.can_move(e1, Position::new_position(7, 1)));
+
+        my_board.place_piece_at(Piece::Bishop(Color::Black), 3, 4);
+        assert!(!my_board.can_move(e1, Position::new_position(7, 1)));
+
+        let mut my_board = castle_position();
+        my_board.place_piece_at(Piece::Rook(Color::Black), 5, 5);
+        assert!(my_board.legal_moves().iter().all(|mv| !mv.is_castle()));
+
+        let mut my_board = castle_position();
+        my_board.place_piece_at(Piece::Rook(Color::Black), 3, 5);
+        assert!(!my_board.can_move(e1, Position::new_position(3, 1)));
+        my_board.place_piece_at(Piece::Rook(Color::Black), 2, 5);
+        my_board.remove_piece(Position::new_position(3, 5));
+        assert!(my_board.can_move(e1, Position::new_position(3, 1)));
+    }
 
+    #[test]
+    fn capturing_rook_disables_castle(){
+        let mut my_board = castle_position();
+        my_board.place_piece_at(Piece::Knight(Color::White), 7, 6);
+        my_board.make_move(Move::new(Position::new_position(7, 6), Position::new_position(8, 8)));
+        assert!(!my_board.black_castle.is_short_castle_enable());
+        assert!(my_board.legal_moves().iter().all(|mv| mv.get_flag() != MoveFlag::ShortCastle));
+        assert!(my_board.legal_moves().iter().any(|mv| mv.get_flag() == MoveFlag::LongCastle));
+    }
+
+    #[test]
+    fn pawn_promotions(){
+        let mut my_board = Board::new_board();
+        my_board.place_piece_at(Piece::King(Color::White), 1, 1);
+        my_board.place_piece_at(Piece::King(Color::Black), 1, 3);
+        my_board.place_piece_at(Piece::Pawn(Color::White), 5, 7);
+        my_board.place_piece_at(Piece::Rook(Color::Black), 6, 8);
+        let promotions: Vec<Move> = my_board.legal_moves().into_iter().filter(|mv| mv.get_promotion().is_some()).collect();
+        assert_eq!(promotions.len(), 8);
+
+        let e7 = Position::new_position(5, 7);
+        let e8 = Position::new_position(5, 8);
+        let knight_promotion = my_board.parse_move("e7e8n").unwrap();
+        assert_eq!(knight_promotion.to_string(), "e7e8n");
+        assert!(knight_promotion == Move::new_promotion(e7, e8, Piece::Knight(Color::White)));
+        my_board.make_move(knight_promotion);
+        assert!(my_board.get_piece(e8) == Some(Piece::Knight(Color::White)));
+        my_board.unmake_move();
+        assert!(my_board.get_piece(e7) == Some(Piece::Pawn(Color::White)));
+        assert!(my_board.get_piece(e8).is_none());
+
+        let capture = my_board.parse_move("e7f8Q").unwrap();
+        my_board.make_move(capture);
+        assert!(my_board.get_piece(Position::new_position(6, 8)) == Some(Piece::Queen(Color::White)));
+        my_board.unmake_move();
+        assert!(my_board.get_piece(Position::new_position(6, 8)) == Some(Piece::Rook(Color::Black)));
+
+        assert!(my_board.parse_move("e7e8").is_err());
+        assert!(my_board.parse_move("e7e8k").is_err());
+        assert!(my_board.parse_move("e7e9q").is_err());
+    }
+
+    #[test]
+    fn checkmate_outcome(){
+        let mut my_board = Board::new_board();
+        my_board.initial_position();
+        assert!(my_board.outcome() == Outcome::Ongoing);
+        for text in ["f2f3", "e7e5", "g2g4", "d8h4"] {
+            let mv = my_board.parse_move(text).unwrap();
+            my_board.make_move(mv);
+        }
+        assert!(my_board.outcome() == Outcome::Checkmate(Color::Black));
+    }
+
+    #[test]
+    fn stalemate_outcome(){
+        let mut my_board = Board::new_board();
+        my_board.place_piece_at(Piece::King(Color::Black), 8, 8);
+        my_board.place_piece_at(Piece::Queen(Color::White), 7, 6);
+        my_board.place_piece_at(Piece::King(Color::White), 6, 7);
+        my_board.turn = Color::Black;
+        assert!(my_board.outcome() == Outcome::Stalemate);
+        my_board.turn = Color::White;
+        assert!(my_board.outcome() == Outcome::Ongoing);
+    }
+
+    #[test]
+    fn clocks_follow_moves(){
+        let mut my_board = Board::new_board();
+        my_board.initial_position();
+        for text in ["g1f3", "g8f6", "e2e4"] {
+            let mv = my_board.parse_move(text).unwrap();
+            my_board.make_move(mv);
+        }
+        assert_eq!(my_board.get_fullmove_number(), 2);
+        assert_eq!(my_board.get_halfmove_clock(), 0);
+        my_board.unmake_move();
+        assert_eq!(my_board.get_halfmove_clock(), 2);
+        my_board.unmake_move();
+        assert_eq!(my_board.get_fullmove_number(), 1);
+        assert_eq!(my_board.get_halfmove_clock(), 1);
+    }
+
+    #[test]
+    fn repetition_draws(){
+        let mut my_board = Board::new_board();
+        my_board.initial_position();
+        let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+        for _ in 0..2 {
+            for text in shuffle {
+                assert!(my_board.claimable_draw().is_none());
+                let mv = my_board.parse_move(text).unwrap();
+                my_board.make_move(mv);
+            }
+        }
+        assert!(my_board.claimable_draw() == Some(DrawReason::ThreefoldRepetition));
+        assert!(my_board.outcome() == Outcome::Ongoing);
+        for _ in 0..2 {
+            for text in shuffle {
+                let mv = my_board.parse_move(text).unwrap();
+                my_board.make_move(mv);
+            }
+        }
+        assert!(my_board.outcome() == Outcome::Draw(DrawReason::FivefoldRepetition));
+    }
+
+    #[test]
+    fn fifty_and_seventy_five_moves_draws(){
+        let mut my_board = castle_position();
+        my_board.halfmove_clock = 99;
+        my_board.make_move(my_board.parse_move("a1a2").unwrap());
+        assert!(my_board.claimable_draw() == Some(DrawReason::FiftyMoves));
+        assert!(my_board.outcome() == Outcome::Ongoing);
+        my_board.halfmove_clock = 149;
+        my_board.make_move(my_board.parse_move("a8a7").unwrap());
+        assert!(my_board.outcome() == Outcome::Draw(DrawReason::SeventyFiveMoves));
+    }
+
+    #[test]
+    fn insufficient_material_draws(){
+        let mut my_board = Board::new_board();
+        my_board.place_piece_at(Piece::King(Color::White), 1, 1);
+        my_board.place_piece_at(Piece::King(Color::Black), 8, 8);
+        assert!(my_board.outcome() == Outcome::Draw(DrawReason::InsufficientMaterial));
+        my_board.place_piece_at(Piece::Knight(Color::White), 4, 4);
+        assert!(my_board.outcome() == Outcome::Draw(DrawReason::InsufficientMaterial));
+        my_board.place_piece_at(Piece::Bishop(Color::White), 4, 4);
+        assert!(my_board.outcome() == Outcome::Draw(DrawReason::InsufficientMaterial));
+        my_board.place_piece_at(Piece::Bishop(Color::Black), 5, 5);
+        assert!(my_board.outcome() == Outcome::Draw(DrawReason::InsufficientMaterial));
+        my_board.place_piece_at(Piece::Bishop(Color::Black), 5, 4);
+        assert!(my_board.outcome() == Outcome::Ongoing);
+        my_board.remove_piece(Position::new_position(5, 4));
+        my_board.place_piece_at(Piece::Pawn(Color::Black), 5, 4);
+        assert!(my_board.outcome() == Outcome::Ongoing);
+    }
+
+    #[test]
+    fn kings_cannot_stand_next_to_each_other(){
+        let mut my_board = Board::new_board();
+        my_board.place_piece_at(Piece::King(Color::White), 5, 4);
+        my_board.place_piece_at(Piece::King(Color::Black), 5, 6);
+        let moves = my_board.legal_moves();
+        assert!(moves.iter().all(|mv| mv.get_to().get_y() != 5));
+        assert_eq!(moves.len(), 5);
+    }
+
+    #[test]
+    fn null_moves(){
+        let mut my_board = Board::from_fen("4k3/8/8/3pP3/8/8/8/R3K3 w - d6 0 1").unwrap();
+        let fen = my_board.to_fen();
+        let key = my_board.get_key();
+        let undo = my_board.make_null_move();
+        assert!(my_board.get_turn() == Color::Black);
+        assert!(my_board.get_en_pasant().is_none());
+        assert_eq!(my_board.get_key(), my_board.compute_key());
+        my_board.unmake_null_move(undo);
+        assert_eq!(my_board.to_fen(), fen);
+        assert_eq!(my_board.get_key(), key);
+
+        assert!(my_board.has_non_pawn_material(Color::White));
+        assert!(!my_board.has_non_pawn_material(Color::Black));
+    }
+
+    #[test]
+    fn boards_without_kings(){
+        let mut my_board = Board::new_board();
+        my_board.place_piece_at(Piece::Rook(Color::White), 1, 1);
+        my_board.place_piece_at(Piece::Pawn(Color::Black), 4, 7);
+        assert!(!my_board.is_white_in_check());
+        assert!(!my_board.is_black_in_check());
+        assert_eq!(my_board.legal_moves().len(), 14);
+        let handle = std::thread::spawn(move || my_board.legal_moves().len());
+        assert_eq!(handle.join().unwrap(), 14);
+    }
+}
@@ -0,0 +1,336 @@
+use std::fmt;
+
+use crate::pieces::{Square, Piece, Color};
+use super::{Board, Position, EnPasant, Castle};
+
+pub const INITIAL_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FenError {
+    WrongFieldCount(usize),
+    WrongRankCount(usize),
+    WrongRankLength(i8),
+    UnknownPiece(char),
+    AdjacentDigits(i8),
+    WrongKingCount {white: usize, black: usize},
+    PawnOnBackRank(String),
+    InvalidTurn(String),
+    InvalidCastle(String),
+    InvalidEnPasant(String),
+    InvalidHalfmoveClock(String),
+    InvalidFullmoveNumber(String),
+    InconsistentCastle(char),
+    InconsistentEnPasant(String),
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::WrongFieldCount(count) => write!(f, "Expected 6 fields, found {}", count),
+            Self::WrongRankCount(count) => write!(f, "Expected 8 ranks, found {}", count),
+            Self::WrongRankLength(rank) => write!(f, "Rank {} does not have 8 squares", rank),
+            Self::UnknownPiece(c) => write!(f, "'{}' not a piece!", c),
+            Self::AdjacentDigits(rank) => write!(f, "Rank {} has adjacent digits", rank),
+            Self::WrongKingCount {white, black} => write!(f, "Expected one king per side, found {} white and {} black", white, black),
+            Self::PawnOnBackRank(square) => write!(f, "Pawn on back rank square {}", square),
+            Self::InvalidTurn(turn) => write!(f, "'{}' is not a side to move", turn),
+            Self::InvalidCastle(castle) => write!(f, "'{}' are not castling rights", castle),
+            Self::InvalidEnPasant(square) => write!(f, "'{}' is not an en passant square", square),
+            Self::InvalidHalfmoveClock(clock) => write!(f, "'{}' is not a halfmove clock", clock),
+            Self::InvalidFullmoveNumber(number) => write!(f, "'{}' is not a fullmove number", number),
+            Self::InconsistentCastle(right) => write!(f, "Castling right '{}' without king and rook on their squares", right),
+            Self::InconsistentEnPasant(square) => write!(f, "En passant square {} without a pawn that just double pushed", square),
+        }
+    }
+}
+
+impl std::error::Error for FenError {}
+
+impl Board {
+    pub fn from_fen(fen: &str) -> Result<Board, FenError> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() != 6 {
+            return Err(FenError::WrongFieldCount(fields.len()));
+        }
+        let mut board = Board::new_board();
+        board.board = Self::parse_placement(fields[0])?;
+        board.turn = match fields[1] {
+            "w" => Color::White,
+            "b" => Color::Black,
+            turn => return Err(FenError::InvalidTurn(turn.to_string())),
+        };
+        (board.white_castle, board.black_castle) = Self::parse_castle(fields[2])?;
+        board.en_pasant = Self::parse_en_pasant(fields[3], board.turn)?;
+        board.halfmove_clock = fields[4].parse()
+            .map_err(|_| FenError::InvalidHalfmoveClock(fields[4].to_string()))?;
+        board.fullmove_number = match fields[5].parse() {
+            Ok(number) if number > 0 => number,
+            _ => return Err(FenError::InvalidFullmoveNumber(fields[5].to_string())),
+        };
+        board.validate()?;
+        board.key = board.compute_key();
+        Ok(board)
+    }
+    fn validate(&self) -> Result<(), FenError> {
+        let mut kings = (0, 0);
+        for (row_index, row) in self.board.iter().enumerate() {
+            for (col_index, square) in row.iter().enumerate() {
+                let position = Position::new_position((col_index + 1) as i8, (8 - row_index) as i8);
+                match square.get_piece() {
+                    Some(Piece::King(Color::White)) => kings.0 += 1,
+                    Some(Piece::King(Color::Black)) => kings.1 += 1,
+                    Some(Piece::Pawn(_)) if position.get_y() == 1 || position.get_y() == 8 => {
+                        return Err(FenError::PawnOnBackRank(position.display()));
+                    },
+                    _ => {},
+                }
+            }
+        }
+        if kings != (1, 1) {
+            return Err(FenError::WrongKingCount {white: kings.0, black: kings.1});
+        }
+        let rights = [
+            ('K', self.white_castle.is_short_castle_enable(), Piece::Rook(Color::White), 8, 1),
+            ('Q', self.white_castle.is_long_castle_enable(), Piece::Rook(Color::White), 1, 1),
+            ('k', self.black_castle.is_short_castle_enable(), Piece::Rook(Color::Black), 8, 8),
+            ('q', self.black_castle.is_long_castle_enable(), Piece::Rook(Color::Black), 1, 8),
+        ];
+        for (right, enabled, rook, rook_x, y) in rights {
+            let king = Piece::King(rook.get_color());
+            if enabled && (self.get_piece(Position::new_position(5, y)) != Some(king) ||
+                self.get_piece(Position::new_position(rook_x, y)) != Some(rook)) {
+                return Err(FenError::InconsistentCastle(right));
+            }
+        }
+        if let EnPasant::Enable(target) = self.en_pasant {
+            let (pawn_y, origin_y) = match self.turn {
+                Color::White => (5, 7),
+                Color::Black => (4, 2),
+            };
+            let pawn = Piece::Pawn(self.turn.opposite());
+            if self.get_piece(Position::new_position(target.get_x(), pawn_y)) != Some(pawn) ||
+                self.get_piece(target).is_some() ||
+                self.get_piece(Position::new_position(target.get_x(), origin_y)).is_some() {
+                return Err(FenError::InconsistentEnPasant(target.display()));
+            }
+        }
+        Ok(())
+    }
+    pub fn to_fen(&self) -> String {
+        let turn = match self.turn {
+            Color::White => "w",
+            Color::Black => "b",
+        };
+        let en_pasant = match self.en_pasant {
+            EnPasant::Enable(position) => position.display(),
+            EnPasant::Disable => String::from("-"),
+        };
+        format!("{} {} {} {} {} {}",
+            self.placement_to_fen(), turn, self.castle_to_fen(), en_pasant,
+            self.halfmove_clock, self.fullmove_number)
+    }
+    fn placement_to_fen(&self) -> String {
+        let mut ranks: Vec<String> = Vec::new();
+        for row in self.board.iter() {
+            let mut rank = String::new();
+            let mut empty_squares = 0;
+            for square in row.iter() {
+                match square.get_piece() {
+                    Some(piece) => {
+                        if empty_squares > 0 {
+                            rank.push_str(&empty_squares.to_string());
+                            empty_squares = 0;
+                        }
+                        rank.push_str(&piece.display());
+                    },
+                    None => empty_squares += 1,
+                }
+            }
+            if empty_squares > 0 {
+                rank.push_str(&empty_squares.to_string());
+            }
+            ranks.push(rank);
+        }
+        ranks.join("/")
+    }
+    fn castle_to_fen(&self) -> String {
+        let mut castle = String::new();
+        if self.white_castle.is_short_castle_enable() {
+            castle.push('K');
+        }
+        if self.white_castle.is_long_castle_enable() {
+            castle.push('Q');
+        }
+        if self.black_castle.is_short_castle_enable() {
+            castle.push('k');
+        }
+        if self.black_castle.is_long_castle_enable() {
+            castle.push('q');
+        }
+        if castle.is_empty() {
+            castle.push('-');
+        }
+        castle
+    }
+    fn parse_placement(placement: &str) -> Result<[[ Square ; 8] ; 8], FenError> {
+        let ranks: Vec<&str> = placement.split('/').collect();
+        if ranks.len() != 8 {
+            return Err(FenError::WrongRankCount(ranks.len()));
+        }
+        let mut squares = [[ Square::Empty ; 8] ; 8];
+        for (row_index, rank) in ranks.iter().enumerate() {
+            let y = 8 - row_index as i8;
+            let mut x: i8 = 1;
+            let mut after_digit = false;
+            for c in rank.chars() {
+                if x > 8 {
+                    return Err(FenError::WrongRankLength(y));
+                }
+                match c {
+                    '1'..='8' if after_digit => return Err(FenError::AdjacentDigits(y)),
+                    '1'..='8' => {
+                        x += c.to_digit(10).unwrap() as i8;
+                        after_digit = true;
+                    },
+                    _ => {
+                        after_digit = false;
+                        let piece = Piece::piece_from_char(c)
+                            .map_err(|_| FenError::UnknownPiece(c))?;
+                        let position = Position::new_position(x, y);
+                        squares[position.get_y_board()][position.get_x_board()] = Square::NonEmpty(piece);
+                        x += 1;
+                    },
+                }
+            }
+            if x != 9 {
+                return Err(FenError::WrongRankLength(y));
+            }
+        }
+        Ok(squares)
+    }
+    fn parse_castle(castle: &str) -> Result<(Castle, Castle), FenError> {
+        let mut white_castle = Castle {long: false, short: false};
+        let mut black_castle = Castle {long: false, short: false};
+        if castle == "-" {
+            return Ok((white_castle, black_castle));
+        }
+        for c in castle.chars() {
+            let right = match c {
+                'K' => &mut white_castle.short,
+                'Q' => &mut white_castle.long,
+                'k' => &mut black_castle.short,
+                'q' => &mut black_castle.long,
+                _ => return Err(FenError::InvalidCastle(castle.to_string())),
+            };
+            if *right {
+                return Err(FenError::InvalidCastle(castle.to_string()));
+            }
+            *right = true;
+        }
+        Ok((white_castle, black_castle))
+    }
+    fn parse_en_pasant(en_pasant: &str, turn: Color) -> Result<EnPasant, FenError> {
+        if en_pasant == "-" {
+            return Ok(EnPasant::Disable);
+        }
+        let position = Position::from_algebraic(en_pasant)
+            .map_err(|_| FenError::InvalidEnPasant(en_pasant.to_string()))?;
+        let expected_row = match turn {
+            Color::White => 6,
+            Color::Black => 3,
+        };
+        if position.get_y() != expected_row {
+            return Err(FenError::InvalidEnPasant(en_pasant.to_string()));
+        }
+        Ok(EnPasant::Enable(position))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_initial_fen(){
+        let my_board = Board::from_fen(INITIAL_FEN).unwrap();
+        assert!(my_board.get_turn() == Color::White);
+        assert!(my_board.get_piece(Position::new_position(5, 1)) == Some(Piece::King(Color::White)));
+        assert!(my_board.get_piece(Position::new_position(4, 8)) == Some(Piece::Queen(Color::Black)));
+        assert!(my_board.get_piece(Position::new_position(5, 4)).is_none());
+        assert!(my_board.get_castle(Color::Black).is_long_castle_enable());
+        assert!(my_board.get_en_pasant().is_none());
+        assert_eq!(my_board.legal_moves().len(), 20);
+    }
+
+    #[test]
+    fn parse_all_fields(){
+        let my_board = Board::from_fen("rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w Kq d6 0 3").unwrap();
+        assert!(my_board.get_en_pasant() == Some(Position::new_position(4, 6)));
+        assert!(my_board.get_castle(Color::White).is_short_castle_enable());
+        assert!(!my_board.get_castle(Color::White).is_long_castle_enable());
+        assert!(!my_board.get_castle(Color::Black).is_short_castle_enable());
+        assert!(my_board.get_castle(Color::Black).is_long_castle_enable());
+        assert_eq!(my_board.get_halfmove_clock(), 0);
+        assert_eq!(my_board.get_fullmove_number(), 3);
+        assert!(my_board.legal_moves().iter().any(|mv| mv.is_en_pasant()));
+    }
+
+    #[test]
+    fn fen_round_trip(){
+        let fens = [
+            INITIAL_FEN,
+            "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w Kq d6 0 3",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            "4k3/8/8/8/8/8/8/4K3 b - - 57 112",
+        ];
+        for fen in fens {
+            assert_eq!(Board::from_fen(fen).unwrap().to_fen(), fen);
+        }
+    }
+
+    #[test]
+    fn fen_after_moves(){
+        let mut my_board = Board::from_fen(INITIAL_FEN).unwrap();
+        for text in ["e2e4", "c7c5", "g1f3"] {
+            let mv = my_board.parse_move(text).unwrap();
+            my_board.make_move(mv);
+        }
+        assert_eq!(my_board.to_fen(), "rnbqkbnr/pp1ppppp/8/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2");
+    }
+
+    #[test]
+    fn fen_errors(){
+        assert_eq!(Board::from_fen("8/8/8/8/8/8/8/8 w - -").err(), Some(FenError::WrongFieldCount(4)));
+        assert_eq!(Board::from_fen("8/8/8/8/8/8/8 w - - 0 1").err(), Some(FenError::WrongRankCount(7)));
+        assert_eq!(Board::from_fen("8/8/8/8/8/8/8/7 w - - 0 1").err(), Some(FenError::WrongRankLength(1)));
+        assert_eq!(Board::from_fen("8/8/8/8/8/8/8/ppppppppp w - - 0 1").err(), Some(FenError::WrongRankLength(1)));
+        assert_eq!(Board::from_fen("8/8/8/8/8/8/8/7x w - - 0 1").err(), Some(FenError::UnknownPiece('x')));
+        assert_eq!(Board::from_fen("8/8/8/8/8/8/8/8 x - - 0 1").err(), Some(FenError::InvalidTurn("x".to_string())));
+        assert_eq!(Board::from_fen("8/8/8/8/8/8/8/8 w KK - 0 1").err(), Some(FenError::InvalidCastle("KK".to_string())));
+        assert_eq!(Board::from_fen("8/8/8/8/8/8/8/8 w - e9 0 1").err(), Some(FenError::InvalidEnPasant("e9".to_string())));
+        assert_eq!(Board::from_fen("8/8/8/8/8/8/8/8 w - e3 0 1").err(), Some(FenError::InvalidEnPasant("e3".to_string())));
+        assert_eq!(Board::from_fen("8/8/8/8/8/8/8/8 w - - x 1").err(), Some(FenError::InvalidHalfmoveClock("x".to_string())));
+        assert_eq!(Board::from_fen("8/8/8/8/8/8/8/8 w - - 0 0").err(), Some(FenError::InvalidFullmoveNumber("0".to_string())));
+    }
+
+    #[test]
+    fn illegal_positions(){
+        let error = |fen: &str| Board::from_fen(fen).err();
+        assert_eq!(error("8/8/8/8/8/8/8/8 w - - 0 1"), Some(FenError::WrongKingCount {white: 0, black: 0}));
+        assert_eq!(error("4k3/8/8/8/8/8/8/8 w - - 0 1"), Some(FenError::WrongKingCount {white: 0, black: 1}));
+        assert_eq!(error("4k3/8/8/8/8/8/8/3KK3 w - - 0 1"), Some(FenError::WrongKingCount {white: 2, black: 1}));
+        assert_eq!(error("4k2P/8/8/8/8/8/8/4K3 w - - 0 1"), Some(FenError::PawnOnBackRank("h8".to_string())));
+        assert_eq!(error("4k3/8/8/8/8/8/8/p3K3 w - - 0 1"), Some(FenError::PawnOnBackRank("a1".to_string())));
+        assert_eq!(error("4k3/8/8/8/44/8/8/4K3 w - - 0 1"), Some(FenError::AdjacentDigits(4)));
+        assert_eq!(error("4k3/8/8/8/8/8/8/4K3 w K - 0 1"), Some(FenError::InconsistentCastle('K')));
+        assert_eq!(error("4k3/8/8/8/8/8/8/R2K4 w Q - 0 1"), Some(FenError::InconsistentCastle('Q')));
+        assert_eq!(error("r3k3/8/8/8/8/8/8/4K3 w kq - 0 1"), Some(FenError::InconsistentCastle('k')));
+        assert_eq!(error("4k3/8/8/8/8/8/8/4K3 w - d6 0 1"), Some(FenError::InconsistentEnPasant("d6".to_string())));
+        assert_eq!(error("4k3/3p4/8/3p4/8/8/8/4K3 w - d6 0 1"), Some(FenError::InconsistentEnPasant("d6".to_string())));
+        assert_eq!(error("4k3/8/8/8/4P3/8/8/4K3 b - d3 0 1"), Some(FenError::InconsistentEnPasant("d3".to_string())));
+        assert!(Board::from_fen("4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1").is_ok());
+        assert!(Board::from_fen("r3k3/8/8/8/8/8/8/4K3 w q - 0 1").is_ok());
+    }
+}
@@ -0,0 +1,251 @@
+use std::fmt;
+
+use crate::pieces::{Piece, Color};
+use super::{Board, Position, Move, MoveFlag};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SanError {
+    InvalidSan(String),
+    IllegalMove(String),
+    AmbiguousMove(String),
+}
+
+impl fmt::Display for SanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidSan(san) => write!(f, "'{}' is not a valid SAN move", san),
+            Self::IllegalMove(san) => write!(f, "'{}' is not a legal move", san),
+            Self::AmbiguousMove(san) => write!(f, "'{}' is ambiguous", san),
+        }
+    }
+}
+
+impl std::error::Error for SanError {}
+
+impl Board {
+    pub fn move_to_san(&self, mv: Move) -> String {
+        let mut san = match mv.get_flag() {
+            MoveFlag::ShortCastle => String::from("O-O"),
+            MoveFlag::LongCastle => String::from("O-O-O"),
+            _ => self.move_to_san_without_check(mv),
+        };
+        let mut board = self.clone();
+        board.make_move(mv);
+        if board.is_in_check(board.turn) {
+            if board.legal_moves().is_empty() {
+                san.push('#');
+            } else {
+                san.push('+');
+            }
+        }
+        san
+    }
+    fn move_to_san_without_check(&self, mv: Move) -> String {
+        let from = mv.get_from();
+        let to = mv.get_to();
+        let piece = self.get_piece(from).unwrap();
+        let is_capture = mv.is_en_pasant() || self.get_piece(to).is_some();
+        let mut san = String::new();
+        if let Piece::Pawn(_) = piece {
+            if is_capture {
+                san.push_str(&from.display()[0..1]);
+            }
+        } else {
+            san.push_str(&piece.display().to_uppercase());
+            san.push_str(&self.disambiguation(mv, piece));
+        }
+        if is_capture {
+            san.push('x');
+        }
+        san.push_str(&to.display());
+        if let Some(promotion) = mv.get_promotion() {
+            san.push('=');
+            san.push_str(&promotion.display().to_uppercase());
+        }
+        san
+    }
+    fn disambiguation(&self, mv: Move, piece: Piece) -> String {
+        let from = mv.get_from();
+        let others: Vec<Position> = self.legal_moves()
+            .into_iter()
+            .filter(|other| other.get_to() == mv.get_to() && other.get_from() != from)
+            .map(|other| other.get_from())
+            .filter(|other_from| self.get_piece(*other_from) == Some(piece))
+            .collect();
+        let square = from.display();
+        if others.is_empty() {
+            String::new()
+        } else if others.iter().all(|other| !other.is_same_column(from)) {
+            square[0..1].to_string()
+        } else if others.iter().all(|other| !other.is_same_row(from)) {
+            square[1..2].to_string()
+        } else {
+            square
+        }
+    }
+    pub fn parse_san(&self, text: &str) -> Result<Move, SanError> {
+        let san = text.trim_end_matches(['+', '#', '!', '?']);
+        match san {
+            "O-O" | "0-0" => return self.find_san_move(text, |mv| mv.get_flag() == MoveFlag::ShortCastle),
+            "O-O-O" | "0-0-0" => return self.find_san_move(text, |mv| mv.get_flag() == MoveFlag::LongCastle),
+            _ => {},
+        }
+        if !san.is_ascii() || san.len() < 2 {
+            return Err(SanError::InvalidSan(text.to_string()));
+        }
+
+        let (san, promotion) = match san.char_indices().last() {
+            Some((index, c)) if "QRBN".contains(c) && index >= 2 => {
+                let san = san[..index].trim_end_matches('=');
+                (san, Some(Self::san_piece(c, self.turn)))
+            },
+            _ => (san, None),
+        };
+        if san.len() < 2 {
+            return Err(SanError::InvalidSan(text.to_string()));
+        }
+        let to = Position::from_algebraic(&san[san.len() - 2..])
+            .map_err(|_| SanError::InvalidSan(text.to_string()))?;
+        let mut prefix = &san[..san.len() - 2];
+        let piece = match prefix.chars().next() {
+            Some(c) if "KQRBN".contains(c) => {
+                prefix = &prefix[1..];
+                Self::san_piece(c, self.turn)
+            },
+            _ => Piece::Pawn(self.turn),
+        };
+        let (prefix, capture) = match prefix.strip_suffix('x') {
+            Some(prefix) => (prefix, true),
+            None => (prefix, false),
+        };
+        let mut from_x = None;
+        let mut from_y = None;
+        for c in prefix.chars() {
+            match c {
+                'a'..='h' if from_x.is_none() && from_y.is_none() => from_x = Some(c as i8 - 'a' as i8 + 1),
+                '1'..='8' if from_y.is_none() => from_y = Some(c as i8 - '1' as i8 + 1),
+                _ => return Err(SanError::InvalidSan(text.to_string())),
+            }
+        }
+
+        let mv = self.find_san_move(text, |mv| {
+            let from = mv.get_from();
+            mv.get_to() == to &&
+            mv.get_promotion() == promotion &&
+            !mv.is_castle() &&
+            self.get_piece(from) == Some(piece) &&
+            from_x.is_none_or(|x| x == from.get_x()) &&
+            from_y.is_none_or(|y| y == from.get_y())
+        })?;
+        if !self.is_standard_prefix(mv, piece, prefix, capture) {
+            return Err(SanError::InvalidSan(text.to_string()));
+        }
+        Ok(mv)
+    }
+    fn is_standard_prefix(&self, mv: Move, piece: Piece, prefix: &str, capture: bool) -> bool {
+        if capture != self.is_capture(mv) {
+            return false;
+        }
+        if piece.is_pawn_of_color(self.turn) {
+            let from_file = mv.get_from().display()[0..1].to_string();
+            return if capture { prefix == from_file } else { prefix.is_empty() };
+        }
+        match (prefix.len(), self.disambiguation(mv, piece).len()) {
+            (0, _) => true,
+            (_, 0) => false,
+            (1, _) => true,
+            (_, needed) => needed == 2,
+        }
+    }
+    fn find_san_move<F: Fn(&Move) -> bool>(&self, text: &str, predicate: F) -> Result<Move, SanError> {
+        let candidates: Vec<Move> = self.legal_moves().into_iter().filter(predicate).collect();
+        match candidates.len() {
+            0 => Err(SanError::IllegalMove(text.to_string())),
+            1 => Ok(candidates[0]),
+            _ => Err(SanError::AmbiguousMove(text.to_string())),
+        }
+    }
+    fn san_piece(c: char, color: Color) -> Piece {
+        match c {
+            'K' => Piece::King(color),
+            'Q' => Piece::Queen(color),
+            'R' => Piece::Rook(color),
+            'B' => Piece::Bishop(color),
+            _ => Piece::Knight(color),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn san_of(fen: &str, text: &str) -> String {
+        let my_board = Board::from_fen(fen).unwrap();
+        let mv = my_board.parse_move(text).unwrap();
+        my_board.move_to_san(mv)
+    }
+
+    #[test]
+    fn encode_san(){
+        assert_eq!(san_of("rnbqkb1r/ppp1pppp/5n2/3p4/3P4/8/PPP1PPPP/RNBQKBNR b KQkq - 0 1", "b8d7"), "Nbd7");
+        assert_eq!(san_of("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2", "e4d5"), "exd5");
+        assert_eq!(san_of("r3kbnr/pppqpppp/2n5/3p1b2/3P1B2/2N5/PPPQPPPP/R3KBNR b KQkq - 0 1", "e8c8"), "O-O-O");
+        assert_eq!(san_of("3k4/4P3/8/8/8/8/8/4K3 w - - 0 1", "e7e8q"), "e8=Q+");
+        assert_eq!(san_of("rnbqkbnr/pppp1ppp/8/4p3/6P1/5P2/PPPPP2P/RNBQKBNR b KQkq - 0 2", "d8h4"), "Qh4#");
+        assert_eq!(san_of("4k3/8/8/8/8/8/8/R4RK1 w - - 0 1", "a1d1"), "Rad1");
+        assert_eq!(san_of("4k3/8/8/R7/8/8/8/R3K3 w Q - 0 1", "a1a3"), "R1a3");
+        assert_eq!(san_of("8/8/1k6/8/4Q2Q/8/8/K6Q w - - 0 1", "h4e1"), "Qh4e1");
+        assert_eq!(san_of("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6"), "exd6");
+    }
+
+    #[test]
+    fn decode_san(){
+        let my_board = Board::from_fen("rnbqkb1r/ppp1pppp/5n2/3p4/3P4/8/PPP1PPPP/RNBQKBNR b KQkq - 0 1").unwrap();
+        let mv = my_board.parse_san("Nbd7").unwrap();
+        assert_eq!(mv.get_from().to_string(), "b8");
+        assert_eq!(my_board.parse_san("N6d7").unwrap().get_from().to_string(), "f6");
+        assert_eq!(my_board.parse_san("Nh5").unwrap().get_from().to_string(), "f6");
+        assert_eq!(my_board.parse_san("Nd7").err(), Some(SanError::AmbiguousMove("Nd7".to_string())));
+        assert_eq!(my_board.parse_san("Nd5").err(), Some(SanError::IllegalMove("Nd5".to_string())));
+        assert_eq!(my_board.parse_san("e4").err(), Some(SanError::IllegalMove("e4".to_string())));
+        assert_eq!(my_board.parse_san("Zz9").err(), Some(SanError::InvalidSan("Zz9".to_string())));
+        assert_eq!(my_board.parse_san("e6").unwrap().get_to().to_string(), "e6");
+        assert_eq!(my_board.parse_san("ee6").err(), Some(SanError::InvalidSan("ee6".to_string())));
+        assert_eq!(my_board.parse_san("e7e6").err(), Some(SanError::InvalidSan("e7e6".to_string())));
+        assert_eq!(my_board.parse_san("Nf6h5").err(), Some(SanError::InvalidSan("Nf6h5".to_string())));
+        assert_eq!(my_board.parse_san("Nfh5").err(), Some(SanError::InvalidSan("Nfh5".to_string())));
+        assert_eq!(my_board.parse_san("Nb8d7").err(), Some(SanError::InvalidSan("Nb8d7".to_string())));
+        assert_eq!(my_board.parse_san("Nxh5").err(), Some(SanError::InvalidSan("Nxh5".to_string())));
+        assert_eq!(my_board.parse_san("dxc2").err(), Some(SanError::IllegalMove("dxc2".to_string())));
+
+        let my_board = Board::from_fen("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2").unwrap();
+        assert_eq!(my_board.parse_san("exd5").unwrap().get_to().to_string(), "d5");
+        assert_eq!(my_board.parse_san("ed5").err(), Some(SanError::InvalidSan("ed5".to_string())));
+        assert_eq!(my_board.parse_san("4xd5").err(), Some(SanError::InvalidSan("4xd5".to_string())));
+        assert_eq!(my_board.parse_san("cxd5").err(), Some(SanError::IllegalMove("cxd5".to_string())));
+        assert_eq!(my_board.parse_san("fxd5").err(), Some(SanError::IllegalMove("fxd5".to_string())));
+        assert_eq!(my_board.parse_san("e4xd5").err(), Some(SanError::InvalidSan("e4xd5".to_string())));
+        assert_eq!(my_board.parse_san("e4d5").err(), Some(SanError::InvalidSan("e4d5".to_string())));
+        assert_eq!(my_board.parse_san("g1f3").err(), Some(SanError::IllegalMove("g1f3".to_string())));
+
+        let my_board = Board::from_fen("3k4/4P3/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let mv = my_board.parse_san("e8=N").unwrap();
+        assert!(mv.get_promotion() == Some(Piece::Knight(Color::White)));
+        assert!(my_board.parse_san("e8Q+").unwrap().get_promotion() == Some(Piece::Queen(Color::White)));
+        assert!(my_board.parse_san("e8").is_err());
+
+        let my_board = Board::from_fen("r3kbnr/pppqpppp/2n5/3p1b2/3P1B2/2N5/PPPQPPPP/R3KBNR b KQkq - 0 1").unwrap();
+        assert!(my_board.parse_san("O-O-O").unwrap().get_flag() == MoveFlag::LongCastle);
+        assert!(my_board.parse_san("O-O").is_err());
+    }
+
+    #[test]
+    fn san_round_trip(){
+        let my_board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+        for mv in my_board.legal_moves() {
+            let san = my_board.move_to_san(mv);
+            assert!(my_board.parse_san(&san).unwrap() == mv, "{}", san);
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use super::{Board, Move, Position};
+use crate::pieces::{Color, Piece};
+
+const KING_VALUE: i32 = 20000;
+
+impl Board {
+    pub fn see(&self, mv: Move) -> i32 {
+        let target = mv.get_to();
+        let mut board = Board {history: Vec::new(), ..*self};
+        let mut piece = match self.get_piece(mv.get_from()) {
+            Some(piece) => piece,
+            None => return 0,
+        };
+        let mut gains: Vec<i32> = Vec::new();
+        let captured =
+            if mv.is_en_pasant() {
+                let captured_position = Position::new_position(target.get_x(), mv.get_from().get_y());
+                board.remove_piece(captured_position);
+                Piece::Pawn(piece.get_color().opposite()).value()
+            } else {
+                self.get_piece(target).map_or(0, |piece| piece.value())
+        };
+        let mut gain = captured;
+        if let Some(promotion) = mv.get_promotion() {
+            gain += promotion.value() - piece.value();
+            piece = promotion;
+        }
+        gains.push(gain);
+        board.remove_piece(mv.get_from());
+        board.place_piece(piece, target);
+
+        let mut side = piece.get_color().opposite();
+        while let Some(attacker) = board.least_valuable_attacker(target, side) {
+            let previous = *gains.last().unwrap();
+            gains.push(Self::see_value(piece) - previous);
+            piece = board.get_piece(attacker).unwrap();
+            board.remove_piece(attacker);
+            board.place_piece(piece, target);
+            side = side.opposite();
+        }
+        while gains.len() > 1 {
+            let last = gains.pop().unwrap();
+            let previous = gains.last_mut().unwrap();
+            *previous = -(-*previous).max(last);
+        }
+        gains[0]
+    }
+    fn least_valuable_attacker(&self, position: Position, color: Color) -> Option<Position> {
+        self.attackers(position, color)
+            .into_iter()
+            .min_by_key(|attacker| Self::see_value(self.get_piece(*attacker).unwrap()))
+    }
+    fn see_value(piece: Piece) -> i32 {
+        match piece {
+            Piece::King(_) => KING_VALUE,
+            _ => piece.value(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn see(fen: &str, text: &str) -> i32 {
+        let board = Board::from_fen(fen).unwrap();
+        board.see(board.parse_move(text).unwrap())
+    }
+
+    #[test]
+    fn finds_all_attackers(){
+        let board = Board::from_fen("4k3/8/2n2q2/1P6/3r4/2N1PB2/3R4/K7 w - - 0 1").unwrap();
+        let target = Position::from_algebraic("d4").unwrap();
+        let mut white: Vec<String> = board.attackers(target, Color::White).iter().map(|position| position.display()).collect();
+        white.sort();
+        assert_eq!(white, ["d2", "e3"]);
+        let black: Vec<String> = board.attackers(target, Color::Black).iter().map(|position| position.display()).collect();
+        assert_eq!(black, ["c6", "f6"]);
+    }
+
+    #[test]
+    fn simple_exchanges(){
+        assert_eq!(see("4k3/8/8/3p4/8/8/8/3QK3 w - - 0 1", "d1d5"), 100);
+        assert_eq!(see("4k3/8/4p3/3p4/8/8/8/3QK3 w - - 0 1", "d1d5"), 100 - 900);
+        assert_eq!(see("4k3/8/4p3/3r4/4P3/8/8/4K3 w - - 0 1", "e4d5"), 400);
+        assert_eq!(see("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", "a1a8"), 0);
+    }
+
+    #[test]
+    fn x_rays_and_kings(){
+        assert_eq!(see("4k3/3r4/8/3p4/8/8/3R4/3RK3 w - - 0 1", "d2d5"), 100);
+        assert_eq!(see("3rk3/3r4/8/3p4/8/8/3R4/3RK3 w - - 0 1", "d2d5"), -400);
+        assert_eq!(see("4k3/8/8/3p4/4K3/8/8/8 w - - 0 1", "e4d5"), 100);
+        let board = Board::from_fen("4k3/8/4p3/3p4/4K3/8/8/8 w - - 0 1").unwrap();
+        let mv = Move::new(Position::from_algebraic("e4").unwrap(), Position::from_algebraic("d5").unwrap());
+        assert_eq!(board.see(mv), -KING_VALUE + 100);
+    }
+
+    #[test]
+    fn en_pasant_and_promotions(){
+        assert_eq!(see("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6"), 100);
+        assert_eq!(see("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7a8q"), 800 - 900);
+        assert_eq!(see("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7b8q"), 500 + 800);
+    }
+}
@@ -0,0 +1,174 @@
+use super::{Board, Position};
+use crate::pieces::{Color, Piece};
+
+struct Keys {
+    pieces: [[u64; 64]; 12],
+    castles: [u64; 4],
+    en_pasant: [u64; 8],
+    black_to_move: u64,
+}
+
+const KEYS: Keys = Keys::generate(0x2545_f491_4f6c_dd1d);
+
+impl Keys {
+    const fn generate(mut seed: u64) -> Keys {
+        let mut keys = Keys {pieces: [[0; 64]; 12], castles: [0; 4], en_pasant: [0; 8], black_to_move: 0};
+        let mut piece = 0;
+        while piece < 12 {
+            let mut square = 0;
+            while square < 64 {
+                keys.pieces[piece][square] = splitmix64(&mut seed);
+                square += 1;
+            }
+            piece += 1;
+        }
+        let mut index = 0;
+        while index < 4 {
+            keys.castles[index] = splitmix64(&mut seed);
+            index += 1;
+        }
+        index = 0;
+        while index < 8 {
+            keys.en_pasant[index] = splitmix64(&mut seed);
+            index += 1;
+        }
+        keys.black_to_move = splitmix64(&mut seed);
+        keys
+    }
+}
+
+const fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+impl Board {
+    pub fn get_key(&self) -> u64 {
+        self.key
+    }
+    pub fn compute_key(&self) -> u64 {
+        let mut key = self.castle_key() ^ self.en_pasant_key() ^ self.turn_key();
+        for x in 1..9 {
+            for y in 1..9 {
+                let position = Position::new_position(x, y);
+                if let Some(piece) = self.get_piece(position) {
+                    key ^= Self::piece_key(piece, position);
+                }
+            }
+        }
+        key
+    }
+    pub(super) fn piece_key(piece: Piece, position: Position) -> u64 {
+        let kind = match piece {
+            Piece::Pawn(_) => 0,
+            Piece::Knight(_) => 1,
+            Piece::Bishop(_) => 2,
+            Piece::Rook(_) => 3,
+            Piece::Queen(_) => 4,
+            Piece::King(_) => 5,
+        };
+        let color = match piece.get_color() {
+            Color::White => 0,
+            Color::Black => 6,
+        };
+        let square = position.get_y_board() * 8 + position.get_x_board();
+        KEYS.pieces[kind + color][square]
+    }
+    pub(super) fn castle_key(&self) -> u64 {
+        let rights = [
+            self.white_castle.is_short_castle_enable(),
+            self.white_castle.is_long_castle_enable(),
+            self.black_castle.is_short_castle_enable(),
+            self.black_castle.is_long_castle_enable(),
+        ];
+        rights.iter()
+            .zip(KEYS.castles.iter())
+            .filter(|(enabled, _)| **enabled)
+            .fold(0, |key, (_, castle_key)| key ^ castle_key)
+    }
+    pub(super) fn en_pasant_key(&self) -> u64 {
+        match self.get_en_pasant() {
+            Some(position) if self.can_capture_en_pasant() => KEYS.en_pasant[position.get_x_board()],
+            _ => 0,
+        }
+    }
+    pub(super) fn turn_key(&self) -> u64 {
+        match self.turn {
+            Color::White => 0,
+            Color::Black => KEYS.black_to_move,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_keys(board: &mut Board, depth: u32) {
+        assert_eq!(board.get_key(), board.compute_key(), "{}", board.to_fen());
+        if depth == 0 {
+            return;
+        }
+        for mv in board.legal_moves() {
+            let key = board.get_key();
+            board.make_move(mv);
+            check_keys(board, depth - 1);
+            board.unmake_move();
+            assert_eq!(board.get_key(), key);
+        }
+    }
+
+    fn key_after(moves: &[&str]) -> u64 {
+        let mut board = Board::from_fen(crate::board::INITIAL_FEN).unwrap();
+        for text in moves {
+            let mv = board.parse_move(text).unwrap();
+            board.make_move(mv);
+        }
+        board.get_key()
+    }
+
+    #[test]
+    fn incremental_key_matches_recomputed_key(){
+        let fens = [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        ];
+        for fen in fens {
+            check_keys(&mut Board::from_fen(fen).unwrap(), 2);
+        }
+    }
+
+    #[test]
+    fn transpositions_share_a_key(){
+        assert_eq!(key_after(&["g1f3", "g8f6", "f3g1", "f6g8"]), key_after(&[]));
+        assert_eq!(key_after(&["e2e4", "e7e5", "g1f3"]), key_after(&["g1f3", "e7e5", "e2e4"]));
+        assert_ne!(key_after(&["e2e4"]), key_after(&[]));
+    }
+
+    #[test]
+    fn key_covers_turn_castles_and_en_pasant(){
+        let key = |fen: &str| Board::from_fen(fen).unwrap().get_key();
+        let base = key("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        assert_ne!(base, key("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1"));
+        assert_ne!(base, key("r3k2r/8/8/8/8/8/8/R3K2R w Kkq - 0 1"));
+        assert_ne!(base, key("r3k2r/8/8/8/8/8/8/R3K2R w KQk - 0 1"));
+        assert_ne!(key("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1"), key("4k3/8/8/3pP3/8/8/8/4K3 w - - 0 1"));
+        assert_eq!(key("4k3/8/8/3p4/8/8/8/4K3 w - d6 0 1"), key("4k3/8/8/3p4/8/8/8/4K3 w - - 0 1"));
+    }
+
+    #[test]
+    fn board_edits_keep_key_in_sync(){
+        let mut board = Board::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+        board.remove_piece(Position::new_position(5, 5));
+        assert_eq!(board.get_key(), board.compute_key());
+        board.place_piece(Piece::Queen(Color::White), Position::new_position(4, 5));
+        assert_eq!(board.get_key(), board.compute_key());
+        board.set_en_pasant(None);
+        assert_eq!(board.get_key(), board.compute_key());
+    }
+}
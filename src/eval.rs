@@ -0,0 +1,411 @@
+use crate::board::{Board, Position};
+use crate::pieces::{Color, Piece, PIECE_VALUES};
+
+const PAWN: usize = 0;
+const KNIGHT: usize = 1;
+const BISHOP: usize = 2;
+const ROOK: usize = 3;
+const QUEEN: usize = 4;
+const KING: usize = 5;
+
+const PHASE_WEIGHTS: [i32; 6] = [0, 1, 1, 2, 4, 0];
+const MAX_PHASE: i32 = 24;
+
+const MG_TABLES: [[i32; 64]; 6] = [
+    [
+          0,   0,   0,   0,   0,   0,   0,   0,
+         50,  50,  50,  50,  50,  50,  50,  50,
+         10,  10,  20,  30,  30,  20,  10,  10,
+          5,   5,  10,  25,  25,  10,   5,   5,
+          0,   0,   0,  20,  20,   0,   0,   0,
+          5,  -5, -10,   0,   0, -10,  -5,   5,
+          5,  10,  10, -20, -20,  10,  10,   5,
+          0,   0,   0,   0,   0,   0,   0,   0,
+    ],
+    [
+        -50, -40, -30, -30, -30, -30, -40, -50,
+        -40, -20,   0,   0,   0,   0, -20, -40,
+        -30,   0,  10,  15,  15,  10,   0, -30,
+        -30,   5,  15,  20,  20,  15,   5, -30,
+        -30,   0,  15,  20,  20,  15,   0, -30,
+        -30,   5,  10,  15,  15,  10,   5, -30,
+        -40, -20,   0,   5,   5,   0, -20, -40,
+        -50, -40, -30, -30, -30, -30, -40, -50,
+    ],
+    [
+        -20, -10, -10, -10, -10, -10, -10, -20,
+        -10,   0,   0,   0,   0,   0,   0, -10,
+        -10,   0,   5,  10,  10,   5,   0, -10,
+        -10,   5,   5,  10,  10,   5,   5, -10,
+        -10,   0,  10,  10,  10,  10,   0, -10,
+        -10,  10,  10,  10,  10,  10,  10, -10,
+        -10,   5,   0,   0,   0,   0,   5, -10,
+        -20, -10, -10, -10, -10, -10, -10, -20,
+    ],
+    [
+          0,   0,   0,   0,   0,   0,   0,   0,
+          5,  10,  10,  10,  10,  10,  10,   5,
+         -5,   0,   0,   0,   0,   0,   0,  -5,
+         -5,   0,   0,   0,   0,   0,   0,  -5,
+         -5,   0,   0,   0,   0,   0,   0,  -5,
+         -5,   0,   0,   0,   0,   0,   0,  -5,
+         -5,   0,   0,   0,   0,   0,   0,  -5,
+          0,   0,   0,   5,   5,   0,   0,   0,
+    ],
+    [
+        -20, -10, -10,  -5,  -5, -10, -10, -20,
+        -10,   0,   0,   0,   0,   0,   0, -10,
+        -10,   0,   5,   5,   5,   5,   0, -10,
+         -5,   0,   5,   5,   5,   5,   0,  -5,
+          0,   0,   5,   5,   5,   5,   0,  -5,
+        -10,   5,   5,   5,   5,   5,   0, -10,
+        -10,   0,   5,   0,   0,   0,   0, -10,
+        -20, -10, -10,  -5,  -5, -10, -10, -20,
+    ],
+    [
+        -30, -40, -40, -50, -50, -40, -40, -30,
+        -30, -40, -40, -50, -50, -40, -40, -30,
+        -30, -40, -40, -50, -50, -40, -40, -30,
+        -30, -40, -40, -50, -50, -40, -40, -30,
+        -20, -30, -30, -40, -40, -30, -30, -20,
+        -10, -20, -20, -20, -20, -20, -20, -10,
+         20,  20,   0,   0,   0,   0,  20,  20,
+         20,  30,  10,   0,   0,  10,  30,  20,
+    ],
+];
+
+const EG_TABLES: [[i32; 64]; 6] = [
+    [
+          0,   0,   0,   0,   0,   0,   0,   0,
+         80,  80,  80,  80,  80,  80,  80,  80,
+         50,  50,  50,  50,  50,  50,  50,  50,
+         30,  30,  30,  30,  30,  30,  30,  30,
+         15,  15,  15,  15,  15,  15,  15,  15,
+          5,   5,   5,   5,   5,   5,   5,   5,
+          0,   0,   0,   0,   0,   0,   0,   0,
+          0,   0,   0,   0,   0,   0,   0,   0,
+    ],
+    [
+        -50, -40, -30, -30, -30, -30, -40, -50,
+        -40, -20,   0,   0,   0,   0, -20, -40,
+        -30,   0,  10,  15,  15,  10,   0, -30,
+        -30,   5,  15,  20,  20,  15,   5, -30,
+        -30,   0,  15,  20,  20,  15,   0, -30,
+        -30,   5,  10,  15,  15,  10,   5, -30,
+        -40, -20,   0,   5,   5,   0, -20, -40,
+        -50, -40, -30, -30, -30, -30, -40, -50,
+    ],
+    [
+        -20, -10, -10, -10, -10, -10, -10, -20,
+        -10,   0,   0,   0,   0,   0,   0, -10,
+        -10,   0,   5,  10,  10,   5,   0, -10,
+        -10,   5,   5,  10,  10,   5,   5, -10,
+        -10,   0,  10,  10,  10,  10,   0, -10,
+        -10,  10,  10,  10,  10,  10,  10, -10,
+        -10,   5,   0,   0,   0,   0,   5, -10,
+        -20, -10, -10, -10, -10, -10, -10, -20,
+    ],
+    [
+          0,   0,   0,   0,   0,   0,   0,   0,
+          5,   5,   5,   5,   5,   5,   5,   5,
+          0,   0,   0,   0,   0,   0,   0,   0,
+          0,   0,   0,   0,   0,   0,   0,   0,
+          0,   0,   0,   0,   0,   0,   0,   0,
+          0,   0,   0,   0,   0,   0,   0,   0,
+          0,   0,   0,   0,   0,   0,   0,   0,
+          0,   0,   0,   0,   0,   0,   0,   0,
+    ],
+    [
+        -20, -10, -10,  -5,  -5, -10, -10, -20,
+        -10,   0,   0,   0,   0,   0,   0, -10,
+        -10,   0,   5,   5,   5,   5,   0, -10,
+         -5,   0,   5,  10,  10,   5,   0,  -5,
+         -5,   0,   5,  10,  10,   5,   0,  -5,
+        -10,   0,   5,   5,   5,   5,   0, -10,
+        -10,   0,   0,   0,   0,   0,   0, -10,
+        -20, -10, -10,  -5,  -5, -10, -10, -20,
+    ],
+    [
+        -50, -40, -30, -20, -20, -30, -40, -50,
+        -30, -20, -10,   0,   0, -10, -20, -30,
+        -30, -10,  20,  30,  30,  20, -10, -30,
+        -30, -10,  30,  40,  40,  30, -10, -30,
+        -30, -10,  30,  40,  40,  30, -10, -30,
+        -30, -10,  20,  30,  30,  20, -10, -30,
+        -30, -30,   0,   0,   0,   0, -30, -30,
+        -50, -30, -30, -30, -30, -30, -30, -50,
+    ],
+];
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct EvalWeights {
+    pub material_mg: [i32; 6],
+    pub material_eg: [i32; 6],
+    pub tables_mg: [[i32; 64]; 6],
+    pub tables_eg: [[i32; 64]; 6],
+    pub doubled_pawn: (i32, i32),
+    pub isolated_pawn: (i32, i32),
+    pub passed_pawn_mg: [i32; 8],
+    pub passed_pawn_eg: [i32; 8],
+    pub pawn_shield: i32,
+    pub open_file_near_king: i32,
+    pub mobility_mg: [i32; 6],
+    pub mobility_eg: [i32; 6],
+}
+
+impl Default for EvalWeights {
+    fn default() -> Self {
+        EvalWeights {
+            material_mg: PIECE_VALUES,
+            material_eg: [120, 300, 320, 530, 950, 0],
+            tables_mg: MG_TABLES,
+            tables_eg: EG_TABLES,
+            doubled_pawn: (-10, -20),
+            isolated_pawn: (-15, -10),
+            passed_pawn_mg: [0, 5, 10, 15, 25, 40, 60, 0],
+            passed_pawn_eg: [0, 10, 20, 35, 60, 90, 130, 0],
+            pawn_shield: 10,
+            open_file_near_king: -20,
+            mobility_mg: [0, 4, 4, 2, 1, 0],
+            mobility_eg: [0, 3, 4, 4, 2, 0],
+        }
+    }
+}
+
+#[derive(Default)]
+struct Score {
+    mg: i32,
+    eg: i32,
+}
+
+impl Score {
+    fn add(&mut self, mg: i32, eg: i32) {
+        self.mg += mg;
+        self.eg += eg;
+    }
+}
+
+pub fn evaluate(board: &Board) -> i32 {
+    evaluate_with(board, &EvalWeights::default())
+}
+
+pub fn evaluate_with(board: &Board, weights: &EvalWeights) -> i32 {
+    let mut scores = [Score::default(), Score::default()];
+    let mut pawn_files = [[0; 8]; 2];
+    let mut phase = 0;
+    for x in 1..9 {
+        for y in 1..9 {
+            if let Some(Piece::Pawn(color)) = board.get_piece(Position::try_new(x, y).unwrap()) {
+                pawn_files[side(color)][(x - 1) as usize] += 1;
+            }
+        }
+    }
+
+    for x in 1..9 {
+        for y in 1..9 {
+            let position = Position::try_new(x, y).unwrap();
+            let piece = match board.get_piece(position) {
+                Some(piece) => piece,
+                None => continue,
+            };
+            let color = piece.get_color();
+            let kind = piece_index(piece);
+            let square = table_index(position, color);
+            let score = &mut scores[side(color)];
+            phase += PHASE_WEIGHTS[kind];
+            score.add(weights.material_mg[kind], weights.material_eg[kind]);
+            score.add(weights.tables_mg[kind][square], weights.tables_eg[kind][square]);
+            let mobility = board.mobility(position) as i32;
+            score.add(weights.mobility_mg[kind] * mobility, weights.mobility_eg[kind] * mobility);
+            match kind {
+                PAWN => score_pawn(board, position, color, &pawn_files, weights, score),
+                KING => score_king_safety(board, position, color, &pawn_files, weights, score),
+                _ => {},
+            }
+        }
+    }
+
+    for color in [Color::White, Color::Black] {
+        let doubled: i32 = pawn_files[side(color)].iter().map(|count| (count - 1).max(0)).sum();
+        scores[side(color)].add(weights.doubled_pawn.0 * doubled, weights.doubled_pawn.1 * doubled);
+    }
+
+    let phase = phase.min(MAX_PHASE);
+    let white = &scores[side(Color::White)];
+    let black = &scores[side(Color::Black)];
+    let mg = white.mg - black.mg;
+    let eg = white.eg - black.eg;
+    let value = (mg * phase + eg * (MAX_PHASE - phase)) / MAX_PHASE;
+    match board.get_turn() {
+        Color::White => value,
+        Color::Black => -value,
+    }
+}
+
+fn side(color: Color) -> usize {
+    match color {
+        Color::White => 0,
+        Color::Black => 1,
+    }
+}
+
+fn piece_index(piece: Piece) -> usize {
+    match piece {
+        Piece::Pawn(_) => PAWN,
+        Piece::Knight(_) => KNIGHT,
+        Piece::Bishop(_) => BISHOP,
+        Piece::Rook(_) => ROOK,
+        Piece::Queen(_) => QUEEN,
+        Piece::King(_) => KING,
+    }
+}
+
+fn table_index(position: Position, color: Color) -> usize {
+    let rank = match color {
+        Color::White => position.get_y(),
+        Color::Black => 9 - position.get_y(),
+    };
+    ((8 - rank) * 8 + position.get_x() - 1) as usize
+}
+
+fn relative_rank(position: Position, color: Color) -> i8 {
+    match color {
+        Color::White => position.get_y(),
+        Color::Black => 9 - position.get_y(),
+    }
+}
+
+fn score_pawn(board: &Board, position: Position, color: Color, pawn_files: &[[i32; 8]; 2], weights: &EvalWeights, score: &mut Score) {
+    let file = (position.get_x() - 1) as usize;
+    let friendly = &pawn_files[side(color)];
+    let left = file.checked_sub(1).map_or(0, |file| friendly[file]);
+    let right = friendly.get(file + 1).copied().unwrap_or(0);
+    if left == 0 && right == 0 {
+        score.add(weights.isolated_pawn.0, weights.isolated_pawn.1);
+    }
+    if is_passed_pawn(board, position, color) {
+        let rank = (relative_rank(position, color) - 1) as usize;
+        score.add(weights.passed_pawn_mg[rank], weights.passed_pawn_eg[rank]);
+    }
+}
+
+fn is_passed_pawn(board: &Board, position: Position, color: Color) -> bool {
+    let step = match color {
+        Color::White => 1,
+        Color::Black => -1,
+    };
+    for x in position.get_x() - 1..=position.get_x() + 1 {
+        let mut y = position.get_y() + step;
+        while let Ok(square) = Position::try_new(x, y) {
+            if let Some(Piece::Pawn(other)) = board.get_piece(square) {
+                if other != color {
+                    return false;
+                }
+            }
+            y += step;
+        }
+    }
+    true
+}
+
+fn score_king_safety(board: &Board, position: Position, color: Color, pawn_files: &[[i32; 8]; 2], weights: &EvalWeights, score: &mut Score) {
+    let step = match color {
+        Color::White => 1,
+        Color::Black => -1,
+    };
+    for x in position.get_x() - 1..=position.get_x() + 1 {
+        if !(1..9).contains(&x) {
+            continue;
+        }
+        if pawn_files[side(color)][(x - 1) as usize] == 0 {
+            score.add(weights.open_file_near_king, 0);
+        }
+        for distance in 1..3 {
+            if let Ok(square) = Position::try_new(x, position.get_y() + step * distance) {
+                if board.get_piece(square) == Some(Piece::Pawn(color)) {
+                    score.add(weights.pawn_shield, 0);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate_fen(fen: &str) -> i32 {
+        evaluate(&Board::from_fen(fen).unwrap())
+    }
+
+    #[test]
+    fn symmetric_positions_are_equal(){
+        assert_eq!(evaluate_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"), 0);
+        assert_eq!(evaluate_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1"), 0);
+        assert_eq!(evaluate_fen("r1bqkb1r/pppp1ppp/2n2n2/4p3/4P3/2N2N2/PPPP1PPP/R1BQKB1R w KQkq - 4 4"), 0);
+    }
+
+    #[test]
+    fn scores_from_side_to_move(){
+        let white = evaluate_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKB1R w KQkq - 0 1");
+        let black = evaluate_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKB1R b KQkq - 0 1");
+        assert!(white < -250);
+        assert_eq!(white, -black);
+    }
+
+    #[test]
+    fn material_matches_piece_values(){
+        let weights = EvalWeights::default();
+        for piece in [Piece::Pawn(Color::White), Piece::Knight(Color::White), Piece::Bishop(Color::Black), Piece::Rook(Color::Black), Piece::Queen(Color::White), Piece::King(Color::Black)] {
+            assert_eq!(weights.material_mg[piece_index(piece)], piece.value());
+        }
+    }
+
+    #[test]
+    fn pawn_structure(){
+        let healthy = evaluate_fen("4k3/8/8/8/8/8/3PP3/4K3 w - - 0 1");
+        let doubled = evaluate_fen("4k3/8/8/8/8/3P4/3P4/4K3 w - - 0 1");
+        let isolated = evaluate_fen("4k3/8/8/8/8/8/2P1P3/4K3 w - - 0 1");
+        assert!(doubled < healthy);
+        assert!(isolated < healthy);
+
+        let board = Board::from_fen("4k3/8/8/8/3P4/8/8/4K3 w - - 0 1").unwrap();
+        let weights = EvalWeights {passed_pawn_mg: [0; 8], passed_pawn_eg: [0; 8], ..EvalWeights::default()};
+        assert!(evaluate(&board) > evaluate_with(&board, &weights));
+        assert!(is_passed_pawn(&Board::from_fen("4k3/p7/8/8/8/8/3P4/4K3 w - - 0 1").unwrap(), Position::from_algebraic("d2").unwrap(), Color::White));
+        assert!(!is_passed_pawn(&Board::from_fen("4k3/2p5/8/8/8/8/3P4/4K3 w - - 0 1").unwrap(), Position::from_algebraic("d2").unwrap(), Color::White));
+    }
+
+    #[test]
+    fn king_safety_and_mobility(){
+        let sheltered = evaluate_fen("r2qk2r/ppp2ppp/8/8/8/8/PPP2PPP/R2Q1RK1 w kq - 0 1");
+        let exposed = evaluate_fen("r2qk2r/ppp2ppp/8/8/6PP/8/PPP2P2/R2Q1RK1 w kq - 0 1");
+        assert!(sheltered > exposed);
+
+        let free = evaluate_fen("4k3/8/8/8/3B4/8/1P6/4K3 w - - 0 1");
+        let trapped = evaluate_fen("4k3/8/8/8/8/8/1P6/B3K3 w - - 0 1");
+        assert!(free > trapped);
+    }
+
+    #[test]
+    fn phase_tapering(){
+        let center = evaluate_fen("8/8/4k3/8/8/3K4/8/8 w - - 0 1");
+        let corner = evaluate_fen("8/8/4k3/8/8/8/8/K7 w - - 0 1");
+        assert!(center > corner);
+
+        let board = Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQK2R w KQkq - 0 1").unwrap();
+        let castled = Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQ1RK1 w kq - 0 1").unwrap();
+        assert!(evaluate(&castled) > evaluate(&board));
+    }
+
+    #[test]
+    fn weights_are_configurable(){
+        let board = Board::from_fen("4k3/8/8/8/8/8/3P4/4K3 w - - 0 1").unwrap();
+        let mut weights = EvalWeights::default();
+        let default = evaluate_with(&board, &weights);
+        weights.material_mg[PAWN] += 50;
+        weights.material_eg[PAWN] += 50;
+        assert_eq!(evaluate_with(&board, &weights), default + 50);
+    }
+}
@@ -0,0 +1,10 @@
+pub mod pieces;
+pub mod board;
+pub mod pgn;
+pub mod uci;
+pub mod xboard;
+pub mod eval;
+pub mod movepick;
+pub mod search;
+pub mod timeman;
+pub mod tt;
@@ -1,10 +1,24 @@
-mod pieces;
-mod board;
+use std::io::{self, BufRead};
 
-use board::{Board};
+use ajedrez::uci::Uci;
+use ajedrez::xboard::Xboard;
 
 fn main(){
-    let mut my_board = Board::new_board();
-    my_board.initial_position();
-    my_board.display();
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let mut first_line = String::new();
+    if input.read_line(&mut first_line).is_err() {
+        return;
+    }
+    if first_line.trim() == "xboard" {
+        let mut xboard = Xboard::new(io::stdout());
+        if xboard.handle_command(&first_line) {
+            xboard.run(input);
+        }
+    } else {
+        let mut uci = Uci::new(io::stdout());
+        if uci.handle_command(&first_line) {
+            uci.run(input);
+        }
+    }
 }
@@ -0,0 +1,196 @@
+use crate::board::{Board, Move, Position};
+use crate::pieces::Color;
+
+const MAX_HISTORY: i32 = 1 << 20;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Stage {
+    TtMove,
+    Captures,
+    Killers,
+    Quiets,
+    Done,
+}
+
+#[derive(Default)]
+pub struct KillerTable {
+    killers: Vec<[Option<Move>; 2]>,
+}
+
+pub struct HistoryTable {
+    scores: Vec<i32>,
+}
+
+pub struct MovePicker {
+    stage: Stage,
+    tt_move: Option<Move>,
+    captures: Vec<Move>,
+    killers: Vec<Move>,
+    quiets: Vec<Move>,
+}
+
+impl KillerTable {
+    pub fn get(&self, ply: usize) -> [Option<Move>; 2] {
+        self.killers.get(ply).copied().unwrap_or([None, None])
+    }
+    pub fn store(&mut self, ply: usize, mv: Move) {
+        if self.killers.len() <= ply {
+            self.killers.resize(ply + 1, [None, None]);
+        }
+        let killers = &mut self.killers[ply];
+        if killers[0] != Some(mv) {
+            killers[1] = killers[0];
+            killers[0] = Some(mv);
+        }
+    }
+}
+
+impl Default for HistoryTable {
+    fn default() -> Self {
+        HistoryTable {scores: vec![0; 2 * 64 * 64]}
+    }
+}
+
+impl HistoryTable {
+    pub fn get(&self, color: Color, mv: Move) -> i32 {
+        self.scores[Self::index(color, mv)]
+    }
+    pub fn update(&mut self, color: Color, mv: Move, depth: u32) {
+        let bonus = (depth * depth) as i32;
+        let index = Self::index(color, mv);
+        self.scores[index] += bonus;
+        if self.scores[index] >= MAX_HISTORY {
+            self.scores.iter_mut().for_each(|score| *score /= 2);
+        }
+    }
+    fn index(color: Color, mv: Move) -> usize {
+        let side = match color {
+            Color::White => 0,
+            Color::Black => 1,
+        };
+        (side * 64 + square_index(mv.get_from())) * 64 + square_index(mv.get_to())
+    }
+}
+
+fn square_index(position: Position) -> usize {
+    ((position.get_y() - 1) * 8 + position.get_x() - 1) as usize
+}
+
+impl MovePicker {
+    pub fn new(board: &Board, moves: Vec<Move>, tt_move: Option<Move>, killers: [Option<Move>; 2]) -> Self {
+        let tt_move = tt_move.filter(|tt_move| moves.contains(tt_move));
+        let mut captures: Vec<(Move, i32)> = Vec::new();
+        let mut quiets: Vec<Move> = Vec::new();
+        for mv in moves.into_iter().filter(|mv| Some(*mv) != tt_move) {
+            if board.is_capture(mv) || mv.get_promotion().is_some() {
+                captures.push((mv, Self::mvv_lva(board, mv)));
+            } else {
+                quiets.push(mv);
+            }
+        }
+        captures.sort_by_key(|(_, score)| -score);
+        let killers: Vec<Move> = killers.iter()
+            .flatten()
+            .filter(|killer| quiets.contains(killer))
+            .copied()
+            .collect();
+        quiets.retain(|mv| !killers.contains(mv));
+        MovePicker {
+            stage: Stage::TtMove,
+            tt_move,
+            captures: captures.into_iter().rev().map(|(mv, _)| mv).collect(),
+            killers: killers.into_iter().rev().collect(),
+            quiets,
+        }
+    }
+    pub fn next_move(&mut self, history: &HistoryTable, color: Color) -> Option<Move> {
+        loop {
+            let next = match self.stage {
+                Stage::TtMove => self.tt_move.take(),
+                Stage::Captures => self.captures.pop(),
+                Stage::Killers => self.killers.pop(),
+                Stage::Quiets => self.quiets.pop(),
+                Stage::Done => return None,
+            };
+            if next.is_some() {
+                return next;
+            }
+            self.stage = match self.stage {
+                Stage::TtMove => Stage::Captures,
+                Stage::Captures => Stage::Killers,
+                Stage::Killers => {
+                    self.quiets.sort_by_key(|mv| history.get(color, *mv));
+                    Stage::Quiets
+                },
+                Stage::Quiets | Stage::Done => Stage::Done,
+            };
+        }
+    }
+    fn mvv_lva(board: &Board, mv: Move) -> i32 {
+        let victim =
+            if mv.is_en_pasant() {
+                100
+            } else {
+                board.get_piece(mv.get_to()).map_or(0, |piece| piece.value())
+        };
+        let attacker = board.get_piece(mv.get_from()).map_or(0, |piece| piece.value());
+        let promotion = mv.get_promotion().map_or(0, |piece| piece.value());
+        (victim + promotion) * 10 - attacker
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn picked_moves(picker: &mut MovePicker, history: &HistoryTable, color: Color) -> Vec<String> {
+        let mut moves: Vec<String> = Vec::new();
+        while let Some(mv) = picker.next_move(history, color) {
+            moves.push(mv.to_string());
+        }
+        moves
+    }
+
+    #[test]
+    fn staged_order(){
+        let board = Board::from_fen("4k3/8/1q3r2/2P5/4N3/8/8/R3K3 w - - 0 1").unwrap();
+        let mv = |text: &str| board.parse_move(text).unwrap();
+        let mut history = HistoryTable::default();
+        history.update(Color::White, mv("a1a7"), 4);
+        history.update(Color::White, mv("e1d2"), 2);
+        let moves = board.legal_moves();
+        let count = moves.len();
+        let mut picker = MovePicker::new(&board, moves, Some(mv("a1a2")), [Some(mv("e4g5")), None]);
+        let picked = picked_moves(&mut picker, &history, Color::White);
+        assert_eq!(picked.len(), count);
+        assert_eq!(&picked[..5], ["a1a2", "c5b6", "e4f6", "e4g5", "a1a7"]);
+        assert_eq!(picked[5], "e1d2");
+    }
+
+    #[test]
+    fn mvv_lva_ordering(){
+        let board = Board::from_fen("4k3/B7/8/2q1r3/3P4/8/8/7K w - - 0 1").unwrap();
+        let mut picker = MovePicker::new(&board, board.legal_moves(), None, [None, None]);
+        let picked = picked_moves(&mut picker, &HistoryTable::default(), Color::White);
+        assert_eq!(&picked[..3], ["d4c5", "a7c5", "d4e5"]);
+    }
+
+    #[test]
+    fn killers_and_history(){
+        let board = Board::from_fen(crate::board::INITIAL_FEN).unwrap();
+        let mv = |text: &str| board.parse_move(text).unwrap();
+        let mut killers = KillerTable::default();
+        killers.store(3, mv("g1f3"));
+        killers.store(3, mv("b1c3"));
+        killers.store(3, mv("b1c3"));
+        assert!(killers.get(3) == [Some(mv("b1c3")), Some(mv("g1f3"))]);
+        assert!(killers.get(7) == [None, None]);
+
+        let mut history = HistoryTable::default();
+        history.update(Color::White, mv("e2e4"), 3);
+        assert_eq!(history.get(Color::White, mv("e2e4")), 9);
+        assert_eq!(history.get(Color::Black, mv("e2e4")), 0);
+        let mut picker = MovePicker::new(&board, board.legal_moves(), None, killers.get(3));
+        assert_eq!(&picked_moves(&mut picker, &history, Color::White)[..3], ["b1c3", "g1f3", "e2e4"]);
+    }
+}
@@ -0,0 +1,802 @@
+use std::fmt;
+
+use crate::board::{Board, Move, Outcome, INITIAL_FEN};
+use crate::pieces::Color;
+
+pub const SEVEN_TAG_ROSTER: [&str; 7] = ["Event", "Site", "Date", "Round", "White", "Black", "Result"];
+const LINE_WIDTH: usize = 80;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgnError {
+    line: usize,
+    column: usize,
+    message: String,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+    Unknown,
+}
+
+#[derive(Clone)]
+pub struct PgnMove {
+    mv: Move,
+    san: String,
+    nags: Vec<u8>,
+    comments: Vec<String>,
+    variations: Vec<Variation>,
+}
+
+#[derive(Clone, Default)]
+pub struct Variation {
+    comments: Vec<String>,
+    moves: Vec<PgnMove>,
+}
+
+#[derive(Clone)]
+pub struct Game {
+    tags: Vec<(String, String)>,
+    mainline: Variation,
+    result: GameResult,
+    initial: Board,
+}
+
+#[derive(Clone, PartialEq)]
+enum TokenKind {
+    TagOpen,
+    TagClose,
+    Str(String),
+    Symbol(String),
+    Period,
+    Star,
+    Nag(u8),
+    Comment(String),
+    VariationOpen,
+    VariationClose,
+}
+
+struct Token {
+    kind: TokenKind,
+    line: usize,
+    column: usize,
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    index: usize,
+    line: usize,
+    column: usize,
+}
+
+impl PgnError {
+    fn new(line: usize, column: usize, message: String) -> Self {
+        Self {line, column, message}
+    }
+    pub fn get_line(&self) -> usize {
+        self.line
+    }
+    pub fn get_column(&self) -> usize {
+        self.column
+    }
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for PgnError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for PgnError {}
+
+impl GameResult {
+    pub fn from_token(token: &str) -> Option<Self> {
+        match token {
+            "1-0" => Some(Self::WhiteWins),
+            "0-1" => Some(Self::BlackWins),
+            "1/2-1/2" => Some(Self::Draw),
+            "*" => Some(Self::Unknown),
+            _ => None,
+        }
+    }
+    pub fn display(&self) -> String {
+        let token = match self {
+            Self::WhiteWins => "1-0",
+            Self::BlackWins => "0-1",
+            Self::Draw => "1/2-1/2",
+            Self::Unknown => "*",
+        };
+        token.to_string()
+    }
+}
+
+impl PgnMove {
+    pub fn get_move(&self) -> Move {
+        self.mv
+    }
+    pub fn get_san(&self) -> &str {
+        &self.san
+    }
+    pub fn get_nags(&self) -> &[u8] {
+        &self.nags
+    }
+    pub fn get_comments(&self) -> &[String] {
+        &self.comments
+    }
+    pub fn get_variations(&self) -> &[Variation] {
+        &self.variations
+    }
+}
+
+impl Variation {
+    pub fn get_comments(&self) -> &[String] {
+        &self.comments
+    }
+    pub fn get_moves(&self) -> &[PgnMove] {
+        &self.moves
+    }
+}
+
+impl Game {
+    pub fn get_tags(&self) -> &[(String, String)] {
+        &self.tags
+    }
+    pub fn get_tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+    pub fn get_mainline(&self) -> &Variation {
+        &self.mainline
+    }
+    pub fn get_result(&self) -> GameResult {
+        self.result
+    }
+    pub fn initial_board(&self) -> Board {
+        self.initial.clone()
+    }
+    pub fn final_board(&self) -> Board {
+        let mut board = self.initial_board();
+        for pgn_move in self.mainline.get_moves() {
+            board.make_move(pgn_move.get_move());
+        }
+        board
+    }
+    pub fn from_board(board: &Board) -> Game {
+        let moves = board.get_played_moves();
+        let mut initial_board = board.clone();
+        while initial_board.unmake_move().is_some() {}
+        let initial_fen = initial_board.to_fen();
+
+        let result = match board.outcome() {
+            Outcome::Checkmate(Color::White) => GameResult::WhiteWins,
+            Outcome::Checkmate(Color::Black) => GameResult::BlackWins,
+            Outcome::Stalemate | Outcome::Draw(_) => GameResult::Draw,
+            Outcome::Ongoing => GameResult::Unknown,
+        };
+        let mut game = Game {tags: Vec::new(), mainline: Variation::default(), result, initial: initial_board.clone()};
+        for tag in SEVEN_TAG_ROSTER {
+            game.insert_tag(tag, &Self::default_tag_value(tag, result));
+        }
+        if initial_fen != INITIAL_FEN {
+            game.insert_tag("SetUp", "1");
+            game.insert_tag("FEN", &initial_fen);
+        }
+        for mv in moves {
+            let san = initial_board.move_to_san(mv);
+            initial_board.make_move(mv);
+            game.mainline.moves.push(PgnMove {mv, san, nags: Vec::new(), comments: Vec::new(), variations: Vec::new()});
+        }
+        game
+    }
+    pub fn set_tag(&mut self, name: &str, value: &str) -> Result<(), String> {
+        if name == "Result" {
+            self.result = GameResult::from_token(value)
+                .ok_or(format!("'{}' is not a game result", value))?;
+        }
+        if name == "FEN" {
+            let initial = Board::from_fen(value).map_err(|error| format!("Invalid FEN tag: {}", error))?;
+            let mut mainline = self.mainline.clone();
+            Self::replay(&mut initial.clone(), &mut mainline)?;
+            self.initial = initial;
+            self.mainline = mainline;
+        }
+        self.insert_tag(name, value);
+        Ok(())
+    }
+    fn replay(board: &mut Board, variation: &mut Variation) -> Result<(), String> {
+        for pgn_move in variation.moves.iter_mut() {
+            for nested in pgn_move.variations.iter_mut() {
+                Self::replay(&mut board.clone(), nested)?;
+            }
+            let mv = pgn_move.mv;
+            if !board.legal_moves().contains(&mv) {
+                return Err(format!("'{}' is not legal from FEN tag", pgn_move.san));
+            }
+            pgn_move.san = board.move_to_san(mv);
+            board.make_move(mv);
+        }
+        Ok(())
+    }
+    fn insert_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some((_, old_value)) => *old_value = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+    pub fn to_pgn(&self) -> String {
+        let mut pgn = String::new();
+        for tag in SEVEN_TAG_ROSTER {
+            let value = match tag {
+                "Result" => self.result.display(),
+                _ => self.get_tag(tag)
+                    .map(|value| value.to_string())
+                    .unwrap_or(Self::default_tag_value(tag, self.result)),
+            };
+            pgn.push_str(&Self::tag_to_pgn(tag, &value));
+        }
+        for (tag, value) in self.tags.iter() {
+            if !SEVEN_TAG_ROSTER.contains(&tag.as_str()) {
+                pgn.push_str(&Self::tag_to_pgn(tag, value));
+            }
+        }
+        pgn.push('\n');
+
+        let board = self.initial_board();
+        let mut tokens: Vec<String> = Vec::new();
+        Self::variation_tokens(&self.mainline, board.get_fullmove_number(), board.get_turn(), &mut tokens);
+        tokens.push(self.result.display());
+        let mut line = String::new();
+        for token in tokens {
+            if !line.is_empty() && line.len() + 1 + token.len() > LINE_WIDTH {
+                pgn.push_str(&line);
+                pgn.push('\n');
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&token);
+        }
+        pgn.push_str(&line);
+        pgn.push_str("\n\n");
+        pgn
+    }
+    fn default_tag_value(tag: &str, result: GameResult) -> String {
+        match tag {
+            "Date" => String::from("????.??.??"),
+            "Result" => result.display(),
+            _ => String::from("?"),
+        }
+    }
+    fn tag_to_pgn(tag: &str, value: &str) -> String {
+        let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+        format!("[{} \"{}\"]\n", tag, value)
+    }
+    fn variation_tokens(variation: &Variation, mut fullmove: u32, mut turn: Color, tokens: &mut Vec<String>) {
+        for comment in variation.get_comments() {
+            Self::comment_tokens(comment, tokens);
+        }
+        let mut needs_number = true;
+        for pgn_move in variation.get_moves() {
+            match turn {
+                Color::White => tokens.push(format!("{}.", fullmove)),
+                Color::Black if needs_number => tokens.push(format!("{}...", fullmove)),
+                Color::Black => {},
+            }
+            tokens.push(pgn_move.get_san().to_string());
+            for nag in pgn_move.get_nags() {
+                tokens.push(format!("${}", nag));
+            }
+            for comment in pgn_move.get_comments() {
+                Self::comment_tokens(comment, tokens);
+            }
+            for nested in pgn_move.get_variations() {
+                let mut nested_tokens: Vec<String> = Vec::new();
+                Self::variation_tokens(nested, fullmove, turn, &mut nested_tokens);
+                match nested_tokens.len() {
+                    0 => tokens.push(String::from("()")),
+                    last => {
+                        nested_tokens[0].insert(0, '(');
+                        nested_tokens[last - 1].push(')');
+                        tokens.append(&mut nested_tokens);
+                    },
+                }
+            }
+            needs_number = !pgn_move.get_comments().is_empty() || !pgn_move.get_variations().is_empty();
+            if turn == Color::Black {
+                fullmove += 1;
+            }
+            turn = turn.opposite();
+        }
+    }
+    fn comment_tokens(comment: &str, tokens: &mut Vec<String>) {
+        let comment = comment.replace('}', "");
+        let words: Vec<&str> = comment.split_whitespace().collect();
+        if words.is_empty() {
+            tokens.push(String::from("{}"));
+            return;
+        }
+        let last = words.len() - 1;
+        for (index, word) in words.into_iter().enumerate() {
+            let mut token = word.to_string();
+            if index == 0 {
+                token.insert(0, '{');
+            }
+            if index == last {
+                token.push('}');
+            }
+            tokens.push(token);
+        }
+    }
+}
+
+pub fn parse_games(text: &str) -> Result<Vec<Game>, PgnError> {
+    let mut parser = Parser::new(text)?;
+    let mut games: Vec<Game> = Vec::new();
+    while !parser.is_finished() {
+        games.push(parser.parse_game()?);
+    }
+    Ok(games)
+}
+
+pub fn parse_game(text: &str) -> Result<Game, PgnError> {
+    let mut parser = Parser::new(text)?;
+    let game = parser.parse_game()?;
+    if !parser.is_finished() {
+        return Err(parser.error("Expected a single game"));
+    }
+    Ok(game)
+}
+
+fn is_symbol_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "_+#=:-/".contains(c)
+}
+
+fn nag_from_annotation(annotation: &str) -> Option<u8> {
+    match annotation {
+        "!" => Some(1),
+        "?" => Some(2),
+        "!!" => Some(3),
+        "??" => Some(4),
+        "!?" => Some(5),
+        "?!" => Some(6),
+        _ => None,
+    }
+}
+
+fn tokenize(text: &str) -> Result<(Vec<Token>, (usize, usize)), PgnError> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut index = 0;
+    let mut line = 1;
+    let mut column = 1;
+    while index < chars.len() {
+        let c = chars[index];
+        let (start_line, start_column) = (line, column);
+        let start = index;
+        index += 1;
+        column += 1;
+        let kind = match c {
+            '\n' => {
+                line += 1;
+                column = 1;
+                continue;
+            },
+            '%' if start_column == 1 => {
+                while index < chars.len() && chars[index] != '\n' {
+                    index += 1;
+                }
+                continue;
+            },
+            c if c.is_whitespace() => continue,
+            '[' => TokenKind::TagOpen,
+            ']' => TokenKind::TagClose,
+            '(' => TokenKind::VariationOpen,
+            ')' => TokenKind::VariationClose,
+            '.' => TokenKind::Period,
+            '*' => TokenKind::Star,
+            '"' => {
+                let mut value = String::new();
+                loop {
+                    match chars.get(index) {
+                        Some('"') => break,
+                        Some('\\') if matches!(chars.get(index + 1), Some('"') | Some('\\')) => {
+                            value.push(chars[index + 1]);
+                            index += 2;
+                            column += 2;
+                        },
+                        Some('\n') | None => return Err(PgnError::new(start_line, start_column, String::from("Unterminated string"))),
+                        Some(c) => {
+                            value.push(*c);
+                            index += 1;
+                            column += 1;
+                        },
+                    }
+                }
+                index += 1;
+                column += 1;
+                TokenKind::Str(value)
+            },
+            '{' => {
+                let mut comment = String::new();
+                loop {
+                    match chars.get(index) {
+                        Some('}') => break,
+                        Some(c) => {
+                            comment.push(*c);
+                            if *c == '\n' {
+                                line += 1;
+                                column = 1;
+                            } else {
+                                column += 1;
+                            }
+                            index += 1;
+                        },
+                        None => return Err(PgnError::new(start_line, start_column, String::from("Unterminated comment"))),
+                    }
+                }
+                index += 1;
+                column += 1;
+                TokenKind::Comment(comment.trim().to_string())
+            },
+            ';' => {
+                let mut comment = String::new();
+                while index < chars.len() && chars[index] != '\n' {
+                    comment.push(chars[index]);
+                    index += 1;
+                    column += 1;
+                }
+                TokenKind::Comment(comment.trim().to_string())
+            },
+            '$' => {
+                while index < chars.len() && chars[index].is_ascii_digit() {
+                    index += 1;
+                    column += 1;
+                }
+                let digits: String = chars[start + 1..index].iter().collect();
+                match digits.parse() {
+                    Ok(nag) => TokenKind::Nag(nag),
+                    Err(_) => return Err(PgnError::new(start_line, start_column, format!("'${}' is not a NAG", digits))),
+                }
+            },
+            '!' | '?' => {
+                while index < chars.len() && (chars[index] == '!' || chars[index] == '?') {
+                    index += 1;
+                    column += 1;
+                }
+                let annotation: String = chars[start..index].iter().collect();
+                match nag_from_annotation(&annotation) {
+                    Some(nag) => TokenKind::Nag(nag),
+                    None => return Err(PgnError::new(start_line, start_column, format!("'{}' is not an annotation", annotation))),
+                }
+            },
+            c if c.is_ascii_alphanumeric() => {
+                while index < chars.len() && is_symbol_char(chars[index]) {
+                    index += 1;
+                    column += 1;
+                }
+                TokenKind::Symbol(chars[start..index].iter().collect())
+            },
+            c => return Err(PgnError::new(start_line, start_column, format!("Unexpected character '{}'", c))),
+        };
+        tokens.push(Token {kind, line: start_line, column: start_column});
+    }
+    Ok((tokens, (line, column)))
+}
+
+impl Parser {
+    fn new(text: &str) -> Result<Self, PgnError> {
+        let (tokens, (line, column)) = tokenize(text)?;
+        Ok(Self {tokens, index: 0, line, column})
+    }
+    fn is_finished(&self) -> bool {
+        self.index >= self.tokens.len()
+    }
+    fn peek(&self) -> Option<&TokenKind> {
+        self.tokens.get(self.index).map(|token| &token.kind)
+    }
+    fn next(&mut self) -> Option<TokenKind> {
+        let token = self.tokens.get(self.index)?;
+        self.index += 1;
+        Some(token.kind.clone())
+    }
+    fn error(&self, message: &str) -> PgnError {
+        match self.tokens.get(self.index) {
+            Some(token) => PgnError::new(token.line, token.column, message.to_string()),
+            None => PgnError::new(self.line, self.column, message.to_string()),
+        }
+    }
+    fn error_at_previous(&self, message: &str) -> PgnError {
+        let token = &self.tokens[self.index - 1];
+        PgnError::new(token.line, token.column, message.to_string())
+    }
+    fn parse_game(&mut self) -> Result<Game, PgnError> {
+        let tags = self.parse_tags()?;
+        let fen = tags.iter()
+            .find(|(tag, _)| tag == "FEN")
+            .map(|(_, value)| value.as_str())
+            .unwrap_or(INITIAL_FEN);
+        let mut board = Board::from_fen(fen)
+            .map_err(|error| self.error_at_previous(&error.to_string()))?;
+        let initial = board.clone();
+        let mainline = self.parse_variation(&mut board, false)?;
+        let result = match self.next() {
+            Some(TokenKind::Star) => GameResult::Unknown,
+            Some(TokenKind::Symbol(token)) => GameResult::from_token(&token).unwrap(),
+            _ => return Err(self.error("Expected a game result")),
+        };
+        Ok(Game {tags, mainline, result, initial})
+    }
+    fn parse_tags(&mut self) -> Result<Vec<(String, String)>, PgnError> {
+        let mut tags: Vec<(String, String)> = Vec::new();
+        while let Some(TokenKind::TagOpen) = self.peek() {
+            self.next();
+            let name = match self.next() {
+                Some(TokenKind::Symbol(name)) => name,
+                _ => return Err(self.error_at_previous("Expected a tag name")),
+            };
+            let value = match self.next() {
+                Some(TokenKind::Str(value)) => value,
+                _ => return Err(self.error_at_previous("Expected a tag value")),
+            };
+            match self.next() {
+                Some(TokenKind::TagClose) => {},
+                _ => return Err(self.error_at_previous("Expected ']'")),
+            }
+            tags.push((name, value));
+        }
+        Ok(tags)
+    }
+    fn parse_variation(&mut self, board: &mut Board, is_nested: bool) -> Result<Variation, PgnError> {
+        let mut variation = Variation::default();
+        let mut played = 0;
+        loop {
+            match self.peek() {
+                None => break,
+                Some(TokenKind::VariationClose) if is_nested => break,
+                Some(TokenKind::Star) => break,
+                Some(TokenKind::Symbol(symbol)) if GameResult::from_token(symbol).is_some() => break,
+                _ => {},
+            }
+            match self.next().unwrap() {
+                TokenKind::Comment(comment) => match variation.moves.last_mut() {
+                    Some(last) => last.comments.push(comment),
+                    None => variation.comments.push(comment),
+                },
+                TokenKind::Nag(nag) => match variation.moves.last_mut() {
+                    Some(last) => last.nags.push(nag),
+                    None => return Err(self.error_at_previous("Expected a move before the NAG")),
+                },
+                TokenKind::Period => {},
+                TokenKind::Symbol(symbol) if symbol.chars().all(|c| c.is_ascii_digit()) => {},
+                TokenKind::Symbol(symbol) => {
+                    let mv = board.parse_san(&symbol)
+                        .map_err(|error| self.error_at_previous(&error.to_string()))?;
+                    let san = board.move_to_san(mv);
+                    board.make_move(mv);
+                    played += 1;
+                    variation.moves.push(PgnMove {mv, san, nags: Vec::new(), comments: Vec::new(), variations: Vec::new()});
+                },
+                TokenKind::VariationOpen => {
+                    if variation.moves.is_empty() {
+                        return Err(self.error_at_previous("Expected a move before the variation"));
+                    }
+                    let last = board.unmake_move().unwrap();
+                    let nested = self.parse_variation(board, true)?;
+                    match self.next() {
+                        Some(TokenKind::VariationClose) => {},
+                        _ => return Err(self.error("Expected ')'")),
+                    }
+                    board.make_move(last);
+                    variation.moves.last_mut().unwrap().variations.push(nested);
+                },
+                _ => return Err(self.error_at_previous("Unexpected token in movetext")),
+            }
+        }
+        if is_nested {
+            for _ in 0..played {
+                board.unmake_move();
+            }
+        }
+        Ok(variation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHOLARS_MATE: &str = r#"[Event "Casual game"]
+[Site "Buenos Aires"]
+[Date "2024.05.01"]
+[Round "1"]
+[White "Alice"]
+[Black "Bob"]
+[Result "1-0"]
+[TimeControl "300+2"]
+
+1. e4 e5 2. Bc4 {Aiming at f7} Nc6 3. Qh5 Nf6?? (3... g6 4. Qf3 Nf6) ; a blunder
+4. Qxf7# $1 1-0
+"#;
+
+    #[test]
+    fn parse_single_game(){
+        let game = parse_game(SCHOLARS_MATE).unwrap();
+        assert_eq!(game.get_tag("White"), Some("Alice"));
+        assert_eq!(game.get_tag("TimeControl"), Some("300+2"));
+        assert_eq!(game.get_tags().len(), 8);
+        assert!(game.get_result() == GameResult::WhiteWins);
+
+        let moves = game.get_mainline().get_moves();
+        assert_eq!(moves.len(), 7);
+        assert_eq!(moves[2].get_comments(), ["Aiming at f7"]);
+        assert_eq!(moves[5].get_san(), "Nf6");
+        assert_eq!(moves[5].get_nags(), [4]);
+        assert_eq!(moves[5].get_comments(), ["a blunder"]);
+        assert_eq!(moves[6].get_san(), "Qxf7#");
+        assert_eq!(moves[6].get_nags(), [1]);
+
+        let variation = &moves[5].get_variations()[0];
+        let sans: Vec<&str> = variation.get_moves().iter().map(|mv| mv.get_san()).collect();
+        assert_eq!(sans, ["g6", "Qf3", "Nf6"]);
+        assert!(game.final_board().outcome() == Outcome::Checkmate(Color::White));
+    }
+
+    #[test]
+    fn parse_multiple_games_and_nested_variations(){
+        let text = r#"[Event "First"]
+[Result "*"]
+
+1. d4 (1. e4 e5 (1... c5 2. Nf3) 2. Nf3) d5 *
+
+[Event "Second"]
+[SetUp "1"]
+[FEN "4k3/8/8/8/8/8/8/R3K3 w Q - 0 1"]
+[Result "1/2-1/2"]
+
+{Start} 1. O-O-O Kf7 1/2-1/2
+"#;
+        let games = parse_games(text).unwrap();
+        assert_eq!(games.len(), 2);
+        let first = games[0].get_mainline().get_moves();
+        assert_eq!(first.len(), 2);
+        let variation = &first[0].get_variations()[0];
+        assert_eq!(variation.get_moves()[0].get_san(), "e4");
+        assert_eq!(variation.get_moves()[1].get_variations()[0].get_moves()[0].get_san(), "c5");
+        assert!(games[0].get_result() == GameResult::Unknown);
+
+        assert_eq!(games[1].get_mainline().get_comments(), ["Start"]);
+        assert_eq!(games[1].get_mainline().get_moves()[0].get_san(), "O-O-O");
+        assert!(games[1].get_result() == GameResult::Draw);
+        assert_eq!(games[1].final_board().to_fen(), "8/5k2/8/8/8/8/8/2KR4 w - - 2 2");
+    }
+
+    #[test]
+    fn errors_report_line_and_column(){
+        let error = parse_game("[Event \"x\"]\n\n1. e4 e5 2. Ke3 *").err().unwrap();
+        assert_eq!((error.get_line(), error.get_column()), (3, 13));
+        let error = parse_game("1. e4 {unterminated").err().unwrap();
+        assert_eq!((error.get_line(), error.get_column()), (1, 7));
+        let error = parse_game("1. e4 e5").err().unwrap();
+        assert_eq!(error.get_message(), "Expected a game result");
+        let error = parse_game("[Event \"x\"\n1. e4 *").err().unwrap();
+        assert_eq!((error.get_line(), error.get_column()), (2, 1));
+        let error = parse_game("1. e4 (1. d4 *").err().unwrap();
+        assert_eq!(error.get_message(), "Expected ')'");
+        assert_eq!((error.get_line(), error.get_column()), (1, 15));
+        let error = parse_game("1. e4 (").err().unwrap();
+        assert_eq!((error.get_line(), error.get_column()), (1, 8));
+        let error = parse_game("[Event \"x\"]\n\n1. e4 e5\n").err().unwrap();
+        assert_eq!(error.get_message(), "Expected a game result");
+        assert_eq!((error.get_line(), error.get_column()), (4, 1));
+    }
+
+    #[test]
+    fn export_comments_with_braces(){
+        let mut game = parse_game("1. e4 {fine} *").unwrap();
+        game.mainline.moves[0].comments[0] = String::from("a {nested} comment }");
+        let pgn = game.to_pgn();
+        assert!(pgn.contains("1. e4 {a {nested comment} *"));
+        let reparsed = parse_game(&pgn).unwrap();
+        assert_eq!(reparsed.get_mainline().get_moves()[0].get_comments(), ["a {nested comment"]);
+        assert_eq!(reparsed.to_pgn(), pgn);
+    }
+
+    #[test]
+    fn export_game(){
+        let game = parse_game(SCHOLARS_MATE).unwrap();
+        let expected = r#"[Event "Casual game"]
+[Site "Buenos Aires"]
+[Date "2024.05.01"]
+[Round "1"]
+[White "Alice"]
+[Black "Bob"]
+[Result "1-0"]
+[TimeControl "300+2"]
+
+1. e4 e5 2. Bc4 {Aiming at f7} 2... Nc6 3. Qh5 Nf6 $4 {a blunder} (3... g6 4.
+Qf3 Nf6) 4. Qxf7# $1 1-0
+
+"#;
+        assert_eq!(game.to_pgn(), expected);
+        let reparsed = parse_game(&game.to_pgn()).unwrap();
+        assert_eq!(reparsed.to_pgn(), expected);
+    }
+
+    #[test]
+    fn export_played_game(){
+        let mut my_board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 b Q - 0 30").unwrap();
+        for text in ["e8f7", "e1c1", "f7e6", "d1d7"] {
+            let mv = my_board.parse_move(text).unwrap();
+            my_board.make_move(mv);
+        }
+        let mut game = Game::from_board(&my_board);
+        game.set_tag("White", "Engine \"A\"").unwrap();
+        let expected = r#"[Event "?"]
+[Site "?"]
+[Date "????.??.??"]
+[Round "?"]
+[White "Engine \"A\""]
+[Black "?"]
+[Result "*"]
+[SetUp "1"]
+[FEN "4k3/8/8/8/8/8/8/R3K3 b Q - 0 30"]
+
+30... Kf7 31. O-O-O Ke6 32. Rd7 *
+
+"#;
+        assert_eq!(game.to_pgn(), expected);
+        assert_eq!(parse_game(expected).unwrap().final_board().to_fen(), my_board.to_fen());
+    }
+
+    #[test]
+    fn invalid_fen_tags_are_rejected(){
+        let mut game = parse_game(SCHOLARS_MATE).unwrap();
+        assert!(game.set_tag("FEN", "garbage").is_err());
+        assert!(game.set_tag("FEN", "4k3/8/8/8/8/8/8/4K3 w - - 0 1").is_err());
+        assert_eq!(game.get_tag("FEN"), None);
+        assert_eq!(game.initial_board().to_fen(), INITIAL_FEN);
+        assert!(game.to_pgn().contains("4. Qxf7#"));
+        assert!(game.final_board().outcome() == Outcome::Checkmate(Color::White));
+
+        let mut game = parse_game("1. e4 (1. Nf3) *").unwrap();
+        assert!(game.set_tag("Result", "2-0").is_err());
+        assert!(game.set_tag("Result", "1/2-1/2").is_ok());
+        assert!(game.get_result() == GameResult::Draw);
+        assert!(game.to_pgn().contains("[Result \"1/2-1/2\"]"));
+        assert!(game.to_pgn().ends_with("Nf3) 1/2-1/2\n\n"));
+        assert!(game.set_tag("FEN", "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").is_err());
+        assert!(game.set_tag("FEN", "4k3/8/8/8/8/8/4P3/4K1N1 w - - 0 1").is_ok());
+        assert!(parse_game(&game.to_pgn()).is_ok());
+
+        let mut game = parse_game("*").unwrap();
+        assert!(game.set_tag("FEN", "4k3/8/8/8/8/8/8/4K3 w - - 0 1").is_ok());
+        assert_eq!(game.final_board().to_fen(), "4k3/8/8/8/8/8/8/4K3 w - - 0 1");
+    }
+
+    #[test]
+    fn export_wraps_long_games(){
+        let mut my_board = Board::new_board();
+        my_board.initial_position();
+        let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+        for _ in 0..10 {
+            for text in shuffle {
+                let mv = my_board.parse_move(text).unwrap();
+                my_board.make_move(mv);
+            }
+        }
+        let pgn = Game::from_board(&my_board).to_pgn();
+        let movetext: Vec<&str> = pgn.lines().skip(8).filter(|line| !line.is_empty()).collect();
+        assert!(movetext.len() > 1);
+        assert!(movetext.iter().all(|line| line.len() <= LINE_WIDTH));
+        assert!(pgn.ends_with("20. Ng1 Ng8 1/2-1/2\n\n"));
+        let game = parse_game(&pgn).unwrap();
+        assert_eq!(game.get_mainline().get_moves().len(), 40);
+    }
+}
@@ -1,11 +1,12 @@
-use crate::board::{Board, Position};
+// Pawn, knight, bishop, rook, queen, king. Shared by the evaluation, SEE and move ordering.
+pub const PIECE_VALUES: [i32; 6] = [100, 320, 330, 500, 900, 0];
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq)]
 pub enum Square {
     Empty,
     NonEmpty(Piece),
 }
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq)]
 pub enum Piece {
     King(Color),
     Queen(Color),
@@ -71,6 +72,16 @@ impl Piece {
             _ => piece.to_string(),
         }
     }
+    pub fn value(&self) -> i32 {
+        match self {
+            Piece::Pawn(_)   => PIECE_VALUES[0],
+            Piece::Knight(_) => PIECE_VALUES[1],
+            Piece::Bishop(_) => PIECE_VALUES[2],
+            Piece::Rook(_)   => PIECE_VALUES[3],
+            Piece::Queen(_)  => PIECE_VALUES[4],
+            Piece::King(_)   => PIECE_VALUES[5],
+        }
+    }
     pub fn get_color(&self) -> Color {
         match self {
             Piece::King(color)   |
@@ -103,11 +114,7 @@ impl Piece {
         }
     }
     pub fn is_king(&self) -> bool {
-        if let Piece::King(_) = *self {
-            true
-        } else {
-            false
-        }
+        matches!(self, Piece::King(_))
     }
     pub fn is_queen_or_rook_of_color(&self, color: Color) -> bool {
         self.is_queen_of_color(color) || self.is_rook_of_color(color)
@@ -0,0 +1,607 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::board::{Board, Move};
+use crate::eval::{evaluate_with, EvalWeights};
+use crate::movepick::{HistoryTable, KillerTable, MovePicker};
+use crate::timeman;
+use crate::tt::{Bound, TranspositionTable};
+
+pub const MAX_DEPTH: u32 = 64;
+pub const MAX_THREADS: usize = 256;
+const MAX_PLY: i32 = 128;
+const ASPIRATION_DEPTH: u32 = 4;
+const ASPIRATION_WINDOW: i32 = 40;
+const REVERSE_FUTILITY_MARGIN: i32 = 120;
+const FUTILITY_MARGINS: [i32; 3] = [0, 150, 300];
+const MATE: i32 = 30000;
+pub(crate) const MATE_BOUND: i32 = MATE - 1000;
+const INFINITY: i32 = MATE + 1;
+
+#[derive(Clone, Copy, Default)]
+pub struct SearchLimits {
+    pub depth: Option<u32>,
+    pub nodes: Option<u64>,
+    pub movetime: Option<Duration>,
+    pub soft_time: Option<Duration>,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SearchOptions {
+    pub null_move: bool,
+    pub late_move_reductions: bool,
+    pub reverse_futility: bool,
+    pub futility: bool,
+    pub principal_variation: bool,
+    pub aspiration_windows: bool,
+    pub threads: usize,
+    pub weights: EvalWeights,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Score {
+    Centipawns(i32),
+    Mate(i32),
+}
+
+#[derive(Clone)]
+pub struct SearchResult {
+    pub best_move: Option<Move>,
+    pub pv: Vec<Move>,
+    pub score: Score,
+    pub depth: u32,
+    pub nodes: u64,
+    pub time: Duration,
+}
+
+struct Searcher<'a> {
+    limits: SearchLimits,
+    options: SearchOptions,
+    tt: &'a TranspositionTable,
+    killers: KillerTable,
+    history: HistoryTable,
+    stop: &'a AtomicBool,
+    start: Instant,
+    nodes: u64,
+    shared_nodes: &'a AtomicU64,
+    published_nodes: u64,
+    aborted: bool,
+    after_null_move: bool,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        SearchOptions {
+            null_move: true,
+            late_move_reductions: true,
+            reverse_futility: true,
+            futility: true,
+            principal_variation: true,
+            aspiration_windows: true,
+            threads: 1,
+            weights: EvalWeights::default(),
+        }
+    }
+}
+
+impl SearchOptions {
+    pub fn none() -> Self {
+        SearchOptions {
+            null_move: false,
+            late_move_reductions: false,
+            reverse_futility: false,
+            futility: false,
+            principal_variation: false,
+            aspiration_windows: false,
+            threads: 1,
+            weights: EvalWeights::default(),
+        }
+    }
+}
+
+impl Score {
+    fn from_value(value: i32) -> Self {
+        if value >= MATE_BOUND {
+            Score::Mate((MATE - value + 1) / 2)
+        } else if value <= -MATE_BOUND {
+            Score::Mate(-(MATE + value) / 2)
+        } else {
+            Score::Centipawns(value)
+        }
+    }
+}
+
+pub fn search<F: FnMut(&SearchResult)>(board: &Board, limits: SearchLimits, tt: &TranspositionTable, stop: &AtomicBool, report: F) -> SearchResult {
+    search_with_options(board, limits, SearchOptions::default(), tt, stop, report)
+}
+
+pub fn search_with_options<F: FnMut(&SearchResult)>(board: &Board, limits: SearchLimits, options: SearchOptions, tt: &TranspositionTable, stop: &AtomicBool, report: F) -> SearchResult {
+    tt.new_search();
+    let helpers_stop = AtomicBool::new(false);
+    let nodes = AtomicU64::new(0);
+    thread::scope(|scope| {
+        let helpers: Vec<_> = (1..options.threads.clamp(1, MAX_THREADS))
+            .map(|id| {
+                let board = board.clone();
+                let (helpers_stop, nodes) = (&helpers_stop, &nodes);
+                scope.spawn(move || helper_search(board, limits.nodes, options, tt, helpers_stop, nodes, 1 + id as u32 % 2))
+            })
+            .collect();
+        let mut result = main_search(board, limits, options, tt, stop, &nodes, report);
+        helpers_stop.store(true, Ordering::Relaxed);
+        for helper in helpers {
+            helper.join().expect("search helper panicked");
+        }
+        result.nodes = nodes.load(Ordering::Relaxed);
+        result
+    })
+}
+
+fn main_search<F: FnMut(&SearchResult)>(board: &Board, limits: SearchLimits, options: SearchOptions, tt: &TranspositionTable, stop: &AtomicBool, nodes: &AtomicU64, mut report: F) -> SearchResult {
+    let mut board = board.clone();
+    let mut searcher = Searcher::new(limits, options, tt, stop, nodes);
+    let root_moves = board.legal_moves();
+    let mut result = SearchResult {
+        best_move: root_moves.first().copied(),
+        pv: root_moves.first().copied().into_iter().collect(),
+        score: Score::Centipawns(0),
+        depth: 0,
+        nodes: 0,
+        time: Duration::ZERO,
+    };
+    if root_moves.is_empty() {
+        result.score = Score::from_value(searcher.no_moves_value(&board, 0));
+        return result;
+    }
+
+    let max_depth = limits.depth.unwrap_or(MAX_DEPTH).clamp(1, MAX_DEPTH);
+    let mut previous_value = 0;
+    let mut best_move_changes = 0.0;
+    for depth in 1..=max_depth {
+        let mut pv: Vec<Move> = Vec::new();
+        let value = searcher.aspiration(&mut board, depth, previous_value, &mut pv);
+        if searcher.aborted {
+            break;
+        }
+        let score_drop = previous_value - value;
+        previous_value = value;
+        best_move_changes /= 2.0;
+        if depth > 1 && pv.first() != result.best_move.as_ref() {
+            best_move_changes += 1.0;
+        }
+        result = SearchResult {
+            best_move: pv.first().copied(),
+            pv,
+            score: Score::from_value(value),
+            depth,
+            nodes: searcher.total_nodes(),
+            time: searcher.start.elapsed(),
+        };
+        report(&result);
+        if let Score::Mate(moves) = result.score {
+            if moves > 0 && (2 * moves - 1) as u32 <= depth {
+                break;
+            }
+        }
+        if let Some(soft_time) = limits.soft_time {
+            if result.time.as_secs_f64() >= soft_time.as_secs_f64() * timeman::time_scale(best_move_changes, score_drop) {
+                break;
+            }
+        }
+    }
+    searcher.publish_nodes();
+    result.time = searcher.start.elapsed();
+    result
+}
+
+// Helpers run the same iterative deepening on their own board copy and only
+// share results with the main thread through the transposition table. Their
+// nodes count towards the main thread's node limit.
+fn helper_search(mut board: Board, node_limit: Option<u64>, options: SearchOptions, tt: &TranspositionTable, stop: &AtomicBool, nodes: &AtomicU64, start_depth: u32) {
+    if board.legal_moves().is_empty() {
+        return;
+    }
+    let limits = SearchLimits {nodes: node_limit, ..SearchLimits::default()};
+    let mut searcher = Searcher::new(limits, options, tt, stop, nodes);
+    let mut previous_value = 0;
+    for depth in start_depth..=MAX_DEPTH {
+        let mut pv: Vec<Move> = Vec::new();
+        previous_value = searcher.aspiration(&mut board, depth, previous_value, &mut pv);
+        if searcher.aborted {
+            break;
+        }
+    }
+    searcher.publish_nodes();
+}
+
+impl<'a> Searcher<'a> {
+    fn new(limits: SearchLimits, options: SearchOptions, tt: &'a TranspositionTable, stop: &'a AtomicBool, shared_nodes: &'a AtomicU64) -> Self {
+        Searcher {
+            limits,
+            options,
+            tt,
+            killers: KillerTable::default(),
+            history: HistoryTable::default(),
+            stop,
+            start: Instant::now(),
+            nodes: 0,
+            shared_nodes,
+            published_nodes: 0,
+            aborted: false,
+            after_null_move: false,
+        }
+    }
+    fn total_nodes(&self) -> u64 {
+        self.shared_nodes.load(Ordering::Relaxed) + self.nodes - self.published_nodes
+    }
+    fn publish_nodes(&mut self) {
+        self.shared_nodes.fetch_add(self.nodes - self.published_nodes, Ordering::Relaxed);
+        self.published_nodes = self.nodes;
+    }
+    fn should_stop(&mut self) -> bool {
+        if self.aborted {
+            return true;
+        }
+        if self.nodes.is_multiple_of(1024) {
+            self.publish_nodes();
+        }
+        let out_of_nodes = self.limits.nodes.is_some_and(|nodes| self.total_nodes() >= nodes);
+        let out_of_time = self.nodes.is_multiple_of(1024) &&
+            self.limits.movetime.is_some_and(|movetime| self.start.elapsed() >= movetime);
+        if out_of_nodes || out_of_time || self.stop.load(Ordering::Relaxed) {
+            self.aborted = true;
+        }
+        self.aborted
+    }
+    fn no_moves_value(&self, board: &Board, ply: i32) -> i32 {
+        if board.is_turn_in_check() {
+            -MATE + ply
+        } else {
+            0
+        }
+    }
+    fn is_draw(board: &Board) -> bool {
+        board.get_halfmove_clock() >= 100 ||
+        board.repetition_count() >= 2 ||
+        board.is_insufficient_material()
+    }
+    fn aspiration(&mut self, board: &mut Board, depth: u32, previous_value: i32, pv: &mut Vec<Move>) -> i32 {
+        if !self.options.aspiration_windows || depth < ASPIRATION_DEPTH || previous_value.abs() >= MATE_BOUND {
+            return self.negamax(board, depth, 0, -INFINITY, INFINITY, pv);
+        }
+        let mut delta = ASPIRATION_WINDOW;
+        let (mut alpha, mut beta) = (previous_value - delta, previous_value + delta);
+        loop {
+            let value = self.negamax(board, depth, 0, alpha, beta, pv);
+            if self.aborted {
+                return value;
+            }
+            if value <= alpha {
+                alpha = (value - delta).max(-INFINITY);
+            } else if value >= beta {
+                beta = (value + delta).min(INFINITY);
+            } else {
+                return value;
+            }
+            delta *= 2;
+        }
+    }
+    fn negamax(&mut self, board: &mut Board, depth: u32, ply: i32, mut alpha: i32, beta: i32, pv: &mut Vec<Move>) -> i32 {
+        pv.clear();
+        let allow_null = !std::mem::replace(&mut self.after_null_move, false);
+        if ply > 0 && self.should_stop() {
+            return 0;
+        }
+        self.nodes += 1;
+        if ply > 0 && Self::is_draw(board) {
+            return 0;
+        }
+        let entry = self.tt.probe(board.get_key());
+        if let Some(entry) = entry.filter(|entry| ply > 0 && entry.get_depth() >= depth) {
+            let score = entry.get_score(ply);
+            let cutoff = match entry.get_bound() {
+                Bound::Exact => true,
+                Bound::Lower => score >= beta,
+                Bound::Upper => score <= alpha,
+            };
+            if cutoff {
+                return score;
+            }
+        }
+        if depth == 0 {
+            return self.quiescence(board, ply, alpha, beta);
+        }
+        let moves = board.legal_moves();
+        if moves.is_empty() {
+            return self.no_moves_value(board, ply);
+        }
+        let turn = board.get_turn();
+        let pv_node = beta - alpha > 1;
+        let in_check = board.is_turn_in_check();
+        let static_eval = if in_check { -INFINITY } else { evaluate_with(board, &self.options.weights) };
+        let prunable = !pv_node && !in_check && beta.abs() < MATE_BOUND;
+
+        if prunable && self.options.reverse_futility && depth <= 3 && static_eval - REVERSE_FUTILITY_MARGIN * depth as i32 >= beta {
+            return static_eval;
+        }
+        if prunable && self.options.null_move && allow_null && depth >= 3 && static_eval >= beta && board.has_non_pawn_material(turn) {
+            let reduction = 2 + depth / 6;
+            let undo = board.make_null_move();
+            let mut null_pv: Vec<Move> = Vec::new();
+            self.after_null_move = true;
+            let value = -self.negamax(board, depth.saturating_sub(1 + reduction), ply + 1, -beta, -beta + 1, &mut null_pv);
+            board.unmake_null_move(undo);
+            if self.aborted {
+                return 0;
+            }
+            if value >= beta {
+                return if value >= MATE_BOUND { beta } else { value };
+            }
+        }
+        let futile = prunable && self.options.futility && depth <= 2 &&
+            static_eval + FUTILITY_MARGINS[depth as usize] <= alpha;
+        let tt_move = entry.and_then(|entry| entry.get_best_move());
+        let mut picker = MovePicker::new(board, moves, tt_move, self.killers.get(ply as usize));
+
+        let original_alpha = alpha;
+        let mut best_value = -INFINITY;
+        let mut best_move = None;
+        let mut child_pv: Vec<Move> = Vec::new();
+        let killers = self.killers.get(ply as usize);
+        let mut moves_searched = 0;
+        while let Some(mv) = picker.next_move(&self.history, turn) {
+            let quiet = !board.is_capture(mv) && mv.get_promotion().is_none();
+            board.make_move(mv);
+            let gives_check = board.is_turn_in_check();
+            if futile && quiet && !gives_check && moves_searched > 0 {
+                board.unmake_move();
+                continue;
+            }
+            let reduction =
+                if self.options.late_move_reductions && depth >= 3 && moves_searched >= 3 &&
+                    quiet && !in_check && !gives_check && !killers.contains(&Some(mv)) {
+                    (1 + u32::from(moves_searched >= 6)).min(depth - 2)
+                } else {
+                    0
+            };
+            let scout_beta = if self.options.principal_variation && moves_searched > 0 { alpha + 1 } else { beta };
+            let mut value = -self.negamax(board, depth - 1 - reduction, ply + 1, -scout_beta, -alpha, &mut child_pv);
+            if reduction > 0 && value > alpha {
+                value = -self.negamax(board, depth - 1, ply + 1, -scout_beta, -alpha, &mut child_pv);
+            }
+            if scout_beta < beta && value > alpha && value < beta {
+                value = -self.negamax(board, depth - 1, ply + 1, -beta, -alpha, &mut child_pv);
+            }
+            board.unmake_move();
+            if self.aborted {
+                return 0;
+            }
+            moves_searched += 1;
+            if value > best_value {
+                best_value = value;
+                best_move = Some(mv);
+                if value > alpha {
+                    alpha = value;
+                    pv.clear();
+                    pv.push(mv);
+                    pv.extend_from_slice(&child_pv);
+                }
+            }
+            if alpha >= beta {
+                if quiet {
+                    self.killers.store(ply as usize, mv);
+                    self.history.update(turn, mv, depth);
+                }
+                break;
+            }
+        }
+        let bound =
+            if best_value >= beta {
+                Bound::Lower
+            } else if best_value > original_alpha {
+                Bound::Exact
+            } else {
+                Bound::Upper
+        };
+        self.tt.store(board.get_key(), depth, bound, best_value, best_move, ply);
+        best_value
+    }
+    fn quiescence(&mut self, board: &mut Board, ply: i32, mut alpha: i32, beta: i32) -> i32 {
+        if self.should_stop() {
+            return 0;
+        }
+        self.nodes += 1;
+        if Self::is_draw(board) {
+            return 0;
+        }
+        let moves = board.legal_moves();
+        if moves.is_empty() {
+            return self.no_moves_value(board, ply);
+        }
+        let in_check = board.is_turn_in_check();
+        let mut best_value = -INFINITY;
+        if !in_check {
+            best_value = evaluate_with(board, &self.options.weights);
+            if best_value >= beta || ply >= MAX_PLY {
+                return best_value;
+            }
+            alpha = alpha.max(best_value);
+        }
+
+        let mut moves: Vec<(Move, i32)> = moves.into_iter()
+            .filter(|mv| in_check || board.is_capture(*mv) || mv.get_promotion().is_some())
+            .map(|mv| (mv, board.see(mv)))
+            .filter(|(_, see)| in_check || *see >= 0)
+            .collect();
+        moves.sort_by_key(|(_, see)| -see);
+        for (mv, _) in moves {
+            board.make_move(mv);
+            let value = -self.quiescence(board, ply + 1, -beta, -alpha);
+            board.unmake_move();
+            if self.aborted {
+                return 0;
+            }
+            if value > best_value {
+                best_value = value;
+                alpha = alpha.max(value);
+            }
+            if alpha >= beta {
+                break;
+            }
+        }
+        best_value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn search_fen(fen: &str, limits: SearchLimits) -> SearchResult {
+        let board = Board::from_fen(fen).unwrap();
+        search(&board, limits, &TranspositionTable::new(1), &AtomicBool::new(false), |_| {})
+    }
+
+    #[test]
+    fn finds_mate_in_one(){
+        let result = search_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", SearchLimits {depth: Some(3), ..SearchLimits::default()});
+        assert_eq!(result.best_move.unwrap().to_string(), "a1a8");
+        assert_eq!(result.score, Score::Mate(1));
+        assert_eq!(result.depth, 1);
+    }
+
+    #[test]
+    fn finds_mate_in_two(){
+        let result = search_fen("r5k1/5ppp/8/8/8/8/1Q6/1R4K1 w - - 0 1", SearchLimits {depth: Some(4), ..SearchLimits::default()});
+        assert_eq!(result.score, Score::Mate(2));
+        assert_eq!(result.pv.len(), 3);
+    }
+
+    #[test]
+    fn sees_being_mated(){
+        let result = search_fen("6k1/5ppp/8/8/8/8/r7/r5K1 w - - 0 1", SearchLimits {depth: Some(2), ..SearchLimits::default()});
+        assert_eq!(result.score, Score::Mate(0));
+        let result = search_fen("8/8/8/8/8/6k1/P4q2/7K w - - 0 1", SearchLimits {depth: Some(3), ..SearchLimits::default()});
+        assert_eq!(result.score, Score::Mate(-1));
+    }
+
+    #[test]
+    fn wins_hanging_material(){
+        let result = search_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1", SearchLimits {depth: Some(2), ..SearchLimits::default()});
+        assert_eq!(result.best_move.unwrap().to_string(), "d2d5");
+        assert!(matches!(result.score, Score::Centipawns(value) if value > 400));
+    }
+
+    #[test]
+    fn avoids_poisoned_captures(){
+        let result = search_fen("4k3/8/4p3/3p4/8/8/8/3QK3 w - - 0 1", SearchLimits {depth: Some(1), ..SearchLimits::default()});
+        assert!(result.best_move.unwrap().to_string() != "d1d5");
+        assert!(matches!(result.score, Score::Centipawns(value) if value > 600));
+    }
+
+    #[test]
+    fn respects_limits(){
+        let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+        let result = search_fen(fen, SearchLimits {nodes: Some(500), ..SearchLimits::default()});
+        assert!(result.nodes <= 500);
+        assert!(result.best_move.is_some());
+
+        let board = Board::from_fen(fen).unwrap();
+        let tt = TranspositionTable::new(1);
+        let result = search(&board, SearchLimits::default(), &tt, &AtomicBool::new(true), |_| {});
+        assert_eq!(result.depth, 0);
+        assert!(result.best_move.is_some());
+
+        let board = Board::from_fen(crate::board::INITIAL_FEN).unwrap();
+        let mut depths: Vec<u32> = Vec::new();
+        search(&board, SearchLimits {depth: Some(2), ..SearchLimits::default()}, &tt, &AtomicBool::new(false), |result| depths.push(result.depth));
+        assert_eq!(depths, [1, 2]);
+    }
+
+    #[test]
+    fn soft_and_hard_time_limits(){
+        let fen = "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3";
+        let result = search_fen(fen, SearchLimits {soft_time: Some(Duration::ZERO), ..SearchLimits::default()});
+        assert_eq!(result.depth, 1);
+
+        let result = search_fen(fen, SearchLimits {movetime: Some(Duration::from_millis(50)), ..SearchLimits::default()});
+        assert!(result.best_move.is_some());
+        assert!(result.depth < MAX_DEPTH);
+        assert!(result.time < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn pruning_options_can_be_toggled(){
+        let board = Board::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3").unwrap();
+        let limits = SearchLimits {depth: Some(4), ..SearchLimits::default()};
+        let run = |options: SearchOptions| {
+            search_with_options(&board, limits, options, &TranspositionTable::new(1), &AtomicBool::new(false), |_| {})
+        };
+        let plain = run(SearchOptions::none());
+        let pruned = run(SearchOptions::default());
+        assert!(pruned.nodes < plain.nodes);
+        let without_pvs = run(SearchOptions {principal_variation: false, ..SearchOptions::default()});
+        assert!(without_pvs.best_move.is_some());
+
+        let board = Board::from_fen("r5k1/5ppp/8/8/8/8/1Q6/1R4K1 w - - 0 1").unwrap();
+        let limits = SearchLimits {depth: Some(4), ..SearchLimits::default()};
+        for options in [SearchOptions::none(), SearchOptions::default()] {
+            let result = search_with_options(&board, limits, options, &TranspositionTable::new(1), &AtomicBool::new(false), |_| {});
+            assert_eq!(result.score, Score::Mate(2));
+        }
+    }
+
+    #[test]
+    fn reuses_transposition_table(){
+        let board = Board::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3").unwrap();
+        let limits = SearchLimits {depth: Some(3), ..SearchLimits::default()};
+        let tt = TranspositionTable::new(1);
+        let first = search(&board, limits, &tt, &AtomicBool::new(false), |_| {});
+        let second = search(&board, limits, &tt, &AtomicBool::new(false), |_| {});
+        assert!(second.nodes < first.nodes);
+        assert!(second.best_move.is_some());
+    }
+
+    #[test]
+    fn helper_threads_share_the_table(){
+        let board = Board::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3").unwrap();
+        let limits = SearchLimits {depth: Some(4), ..SearchLimits::default()};
+        let tt = TranspositionTable::new(1);
+        let mut reports = 0;
+        let options = SearchOptions {threads: 3, ..SearchOptions::default()};
+        let result = search_with_options(&board, limits, options, &tt, &AtomicBool::new(false), |_| reports += 1);
+        assert_eq!(reports, 4);
+        assert_eq!(result.depth, 4);
+        assert!(result.best_move.is_some());
+        assert!(tt.probe(board.get_key()).is_some());
+
+        let board = Board::from_fen("r5k1/5ppp/8/8/8/8/1Q6/1R4K1 w - - 0 1").unwrap();
+        let result = search_with_options(&board, limits, options, &TranspositionTable::new(1), &AtomicBool::new(false), |_| {});
+        assert_eq!(result.score, Score::Mate(2));
+    }
+
+    #[test]
+    fn node_limit_counts_all_threads(){
+        let board = Board::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3").unwrap();
+        let limits = SearchLimits {nodes: Some(5000), ..SearchLimits::default()};
+        let options = SearchOptions {threads: 4, ..SearchOptions::default()};
+        let result = search_with_options(&board, limits, options, &TranspositionTable::new(1), &AtomicBool::new(false), |_| {});
+        assert!(result.best_move.is_some());
+        assert!(result.nodes <= 5000 + 4 * 1024, "{}", result.nodes);
+    }
+
+    #[test]
+    fn searches_with_custom_weights(){
+        let board = Board::from_fen("4k3/8/8/8/8/8/P7/4K3 w - - 0 1").unwrap();
+        let limits = SearchLimits {depth: Some(2), ..SearchLimits::default()};
+        let mut weights = EvalWeights::default();
+        weights.material_mg[0] = 1000;
+        weights.material_eg[0] = 1000;
+        let run = |options: SearchOptions| {
+            search_with_options(&board, limits, options, &TranspositionTable::new(1), &AtomicBool::new(false), |_| {}).score
+        };
+        assert!(matches!(run(SearchOptions::default()), Score::Centipawns(score) if score < 500));
+        assert!(matches!(run(SearchOptions {weights, ..SearchOptions::default()}), Score::Centipawns(score) if score > 900));
+    }
+}
@@ -0,0 +1,75 @@
+use std::time::Duration;
+
+const MOVE_OVERHEAD: Duration = Duration::from_millis(30);
+const DEFAULT_MOVES_TO_GO: u32 = 30;
+const MAX_MOVES_TO_GO: u32 = 50;
+const HARD_LIMIT_FACTOR: u32 = 4;
+const MAX_TIME_SCALE: f64 = 2.5;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TimeLimits {
+    pub soft: Duration,
+    pub hard: Duration,
+}
+
+pub fn allocate(remaining: Duration, increment: Duration, movestogo: Option<u32>) -> TimeLimits {
+    let available = remaining.saturating_sub(MOVE_OVERHEAD);
+    let moves_to_go = movestogo.unwrap_or(DEFAULT_MOVES_TO_GO).clamp(1, MAX_MOVES_TO_GO);
+    let soft = (available / moves_to_go + increment * 3 / 4).min(available / 2);
+    let hard = (soft * HARD_LIMIT_FACTOR).min(available * 3 / 4).max(soft);
+    TimeLimits {soft, hard}
+}
+
+pub fn time_scale(best_move_changes: f64, score_drop: i32) -> f64 {
+    let instability = 1.0 + best_move_changes / 2.0;
+    let falling_score = 1.0 + (score_drop.clamp(0, 100) as f64) / 200.0;
+    (instability * falling_score).min(MAX_TIME_SCALE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn millis(value: u64) -> Duration {
+        Duration::from_millis(value)
+    }
+
+    #[test]
+    fn sudden_death_and_increment(){
+        let limits = allocate(millis(60030), Duration::ZERO, None);
+        assert_eq!(limits.soft, millis(2000));
+        assert_eq!(limits.hard, millis(8000));
+        let limits = allocate(millis(60030), millis(1000), None);
+        assert_eq!(limits.soft, millis(2750));
+        assert_eq!(limits.hard, millis(11000));
+    }
+
+    #[test]
+    fn moves_to_go(){
+        let limits = allocate(millis(10030), Duration::ZERO, Some(5));
+        assert_eq!(limits.soft, millis(2000));
+        assert_eq!(limits.hard, millis(7500));
+        let limits = allocate(millis(10030), Duration::ZERO, Some(1));
+        assert_eq!(limits.soft, millis(5000));
+        assert_eq!(limits.hard, millis(7500));
+    }
+
+    #[test]
+    fn never_exceeds_the_clock(){
+        let limits = allocate(millis(20), millis(5000), None);
+        assert_eq!(limits.soft, Duration::ZERO);
+        assert_eq!(limits.hard, Duration::ZERO);
+        let limits = allocate(millis(1030), millis(5000), None);
+        assert!(limits.soft <= millis(500));
+        assert!(limits.hard <= millis(750));
+    }
+
+    #[test]
+    fn scales_with_instability(){
+        assert_eq!(time_scale(0.0, 0), 1.0);
+        assert_eq!(time_scale(0.0, -50), 1.0);
+        assert!(time_scale(1.0, 0) > 1.0);
+        assert!(time_scale(0.0, 60) > 1.0);
+        assert_eq!(time_scale(10.0, 100), MAX_TIME_SCALE);
+    }
+}
@@ -0,0 +1,288 @@
+use std::mem;
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+
+use crate::board::{Move, MoveFlag, Position};
+use crate::pieces::{Color, Piece};
+use crate::search::MATE_BOUND;
+
+pub const DEFAULT_HASH_MB: usize = 16;
+pub const MAX_HASH_MB: usize = 1024;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+#[derive(Clone, Copy)]
+pub struct Entry {
+    key: u64,
+    depth: u32,
+    bound: Bound,
+    score: i32,
+    best_move: Option<Move>,
+    age: u8,
+}
+
+// Each slot stores the key xored with the data so that a torn write from
+// another thread is detected as a key mismatch instead of a corrupt entry.
+#[derive(Default)]
+struct Slot {
+    check: AtomicU64,
+    data: AtomicU64,
+}
+
+pub struct TranspositionTable {
+    slots: Vec<Slot>,
+    age: AtomicU8,
+}
+
+impl Entry {
+    pub fn get_depth(&self) -> u32 {
+        self.depth
+    }
+    pub fn get_bound(&self) -> Bound {
+        self.bound
+    }
+    pub fn get_score(&self, ply: i32) -> i32 {
+        if self.score >= MATE_BOUND {
+            self.score - ply
+        } else if self.score <= -MATE_BOUND {
+            self.score + ply
+        } else {
+            self.score
+        }
+    }
+    pub fn get_best_move(&self) -> Option<Move> {
+        self.best_move
+    }
+    fn pack(&self) -> u64 {
+        let bound = match self.bound {
+            Bound::Exact => 1,
+            Bound::Lower => 2,
+            Bound::Upper => 3,
+        };
+        (self.score as i16 as u16 as u64)
+            | (self.depth.min(u8::MAX as u32) as u64) << 16
+            | bound << 24
+            | (self.age as u64) << 26
+            | self.best_move.map_or(0, pack_move) << 34
+    }
+    fn unpack(key: u64, data: u64) -> Option<Self> {
+        let bound = match (data >> 24) & 0b11 {
+            1 => Bound::Exact,
+            2 => Bound::Lower,
+            3 => Bound::Upper,
+            _ => return None,
+        };
+        Some(Entry {
+            key,
+            depth: ((data >> 16) & 0xff) as u32,
+            bound,
+            score: data as u16 as i16 as i32,
+            best_move: unpack_move(data >> 34),
+            age: (data >> 26) as u8,
+        })
+    }
+}
+
+fn pack_move(mv: Move) -> u64 {
+    let square = |position: Position| ((position.get_y() - 1) * 8 + position.get_x() - 1) as u64;
+    let promotion = match mv.get_promotion() {
+        None => 0,
+        Some(Piece::Queen(_)) => 1,
+        Some(Piece::Rook(_)) => 2,
+        Some(Piece::Bishop(_)) => 3,
+        Some(_) => 4,
+    };
+    let flag = match mv.get_flag() {
+        MoveFlag::Normal => 0,
+        MoveFlag::DoublePush => 1,
+        MoveFlag::EnPasant => 2,
+        MoveFlag::ShortCastle => 3,
+        MoveFlag::LongCastle => 4,
+    };
+    square(mv.get_from()) | square(mv.get_to()) << 6 | promotion << 12 | flag << 15
+}
+
+fn unpack_move(data: u64) -> Option<Move> {
+    let square = |index: u64| Position::try_new((index % 8) as i8 + 1, (index / 8) as i8 + 1).ok();
+    let from = square(data & 0x3f)?;
+    let to = square((data >> 6) & 0x3f)?;
+    if from == to {
+        return None;
+    }
+    let color = if to.get_y() == 8 { Color::White } else { Color::Black };
+    let promotion = match (data >> 12) & 0b111 {
+        1 => Some(Piece::Queen(color)),
+        2 => Some(Piece::Rook(color)),
+        3 => Some(Piece::Bishop(color)),
+        4 => Some(Piece::Knight(color)),
+        _ => None,
+    };
+    let flag = match (data >> 15) & 0b111 {
+        1 => MoveFlag::DoublePush,
+        2 => MoveFlag::EnPasant,
+        3 => MoveFlag::ShortCastle,
+        4 => MoveFlag::LongCastle,
+        _ => MoveFlag::Normal,
+    };
+    Some(match promotion {
+        Some(piece) => Move::new_promotion(from, to, piece),
+        None => Move::new_with_flag(from, to, flag),
+    })
+}
+
+impl TranspositionTable {
+    pub fn new(megabytes: usize) -> Self {
+        let mut table = TranspositionTable {slots: Vec::new(), age: AtomicU8::new(0)};
+        table.resize(megabytes);
+        table
+    }
+    pub fn resize(&mut self, megabytes: usize) {
+        let bytes = megabytes.clamp(1, MAX_HASH_MB) * 1024 * 1024;
+        let capacity = bytes / mem::size_of::<Slot>();
+        let size = 1 << capacity.ilog2();
+        self.slots = (0..size).map(|_| Slot::default()).collect();
+        self.age = AtomicU8::new(0);
+    }
+    pub fn clear(&mut self) {
+        for slot in self.slots.iter_mut() {
+            *slot = Slot::default();
+        }
+        self.age = AtomicU8::new(0);
+    }
+    pub fn new_search(&self) {
+        self.age.fetch_add(1, Ordering::Relaxed);
+    }
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+    pub fn probe(&self, key: u64) -> Option<Entry> {
+        self.load(key).filter(|entry| entry.key == key)
+    }
+    pub fn store(&self, key: u64, depth: u32, bound: Bound, score: i32, best_move: Option<Move>, ply: i32) {
+        let age = self.age.load(Ordering::Relaxed);
+        let (best_move, replace) = match self.load(key) {
+            None => (best_move, true),
+            Some(entry) if entry.key == key => (best_move.or(entry.best_move), depth >= entry.depth || bound == Bound::Exact || entry.age != age),
+            Some(entry) => (best_move, entry.age != age || depth >= entry.depth),
+        };
+        if !replace {
+            return;
+        }
+        let score =
+            if score >= MATE_BOUND {
+                score + ply
+            } else if score <= -MATE_BOUND {
+                score - ply
+            } else {
+                score
+        };
+        let data = Entry {key, depth, bound, score, best_move, age}.pack();
+        let slot = &self.slots[self.index(key)];
+        slot.check.store(key ^ data, Ordering::Relaxed);
+        slot.data.store(data, Ordering::Relaxed);
+    }
+    fn load(&self, key: u64) -> Option<Entry> {
+        let slot = &self.slots[self.index(key)];
+        let data = slot.data.load(Ordering::Relaxed);
+        Entry::unpack(slot.check.load(Ordering::Relaxed) ^ data, data)
+    }
+    fn index(&self, key: u64) -> usize {
+        (key as usize) & (self.slots.len() - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+
+    #[test]
+    fn size_is_a_power_of_two(){
+        let mut table = TranspositionTable::new(1);
+        assert!(table.len().is_power_of_two());
+        assert!(table.len() * mem::size_of::<Slot>() <= 1024 * 1024);
+        let small = table.len();
+        table.resize(4);
+        assert_eq!(table.len(), small * 4);
+    }
+
+    #[test]
+    fn store_and_probe(){
+        let board = Board::from_fen(crate::board::INITIAL_FEN).unwrap();
+        let mv = board.parse_move("e2e4").unwrap();
+        let mut table = TranspositionTable::new(1);
+        table.store(board.get_key(), 5, Bound::Lower, 35, Some(mv), 0);
+        let entry = table.probe(board.get_key()).unwrap();
+        assert_eq!(entry.get_depth(), 5);
+        assert_eq!(entry.get_bound(), Bound::Lower);
+        assert_eq!(entry.get_score(0), 35);
+        assert!(entry.get_best_move() == Some(mv));
+        assert!(table.probe(board.get_key() ^ 1).is_none());
+        table.clear();
+        assert!(table.probe(board.get_key()).is_none());
+    }
+
+    #[test]
+    fn mate_scores_are_relative_to_the_node(){
+        let table = TranspositionTable::new(1);
+        let mate = MATE_BOUND + 900;
+        table.store(7, 3, Bound::Exact, mate - 4, None, 4);
+        assert_eq!(table.probe(7).unwrap().get_score(4), mate - 4);
+        assert_eq!(table.probe(7).unwrap().get_score(2), mate - 2);
+        table.store(7, 3, Bound::Exact, -mate + 5, None, 5);
+        assert_eq!(table.probe(7).unwrap().get_score(1), -mate + 1);
+    }
+
+    #[test]
+    fn replacement_policy(){
+        let table = TranspositionTable::new(1);
+        let other = 7 + table.len() as u64;
+        table.store(7, 6, Bound::Exact, 10, None, 0);
+        table.store(other, 2, Bound::Exact, 20, None, 0);
+        assert!(table.probe(7).is_some());
+        assert!(table.probe(other).is_none());
+        table.store(7, 1, Bound::Upper, 30, None, 0);
+        assert_eq!(table.probe(7).unwrap().get_depth(), 6);
+        table.new_search();
+        table.store(other, 2, Bound::Exact, 20, None, 0);
+        assert!(table.probe(7).is_none());
+        assert_eq!(table.probe(other).unwrap().get_score(0), 20);
+    }
+
+    #[test]
+    fn moves_survive_packing(){
+        let table = TranspositionTable::new(1);
+        let cases = [
+            ("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", ["e1g1", "e1c1", "a1a8"]),
+            ("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", ["e5d6", "e1d2", "e5e6"]),
+            ("1r2k3/P7/8/8/8/8/4p3/7K b - - 0 1", ["e2e1r", "b8b1", "e8d7"]),
+            ("4k3/P7/8/8/8/8/2P5/4K3 w - - 0 1", ["a7a8n", "a7a8b", "c2c4"]),
+        ];
+        for (fen, moves) in cases {
+            let board = Board::from_fen(fen).unwrap();
+            for (key, text) in moves.iter().enumerate() {
+                let mv = board.parse_move(text).unwrap();
+                table.store(key as u64, 1, Bound::Upper, -35, Some(mv), 0);
+                let entry = table.probe(key as u64).unwrap();
+                assert!(entry.get_best_move() == Some(mv), "{}", text);
+                assert_eq!(entry.get_score(0), -35);
+            }
+        }
+    }
+
+    #[test]
+    fn torn_slots_are_rejected(){
+        let table = TranspositionTable::new(1);
+        table.store(7, 4, Bound::Exact, 10, None, 0);
+        let slot = &table.slots[table.index(7)];
+        slot.data.fetch_xor(1 << 20, Ordering::Relaxed);
+        assert!(table.probe(7).is_none());
+    }
+}
@@ -0,0 +1,401 @@
+use std::io::{BufRead, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::board::{Board, Move, INITIAL_FEN};
+use crate::pieces::Color;
+use crate::search::{self, Score, SearchLimits, SearchOptions, SearchResult, MAX_DEPTH, MAX_THREADS};
+use crate::timeman;
+use crate::tt::{TranspositionTable, DEFAULT_HASH_MB, MAX_HASH_MB};
+
+const ENGINE_NAME: &str = "Ajedrez";
+const ENGINE_AUTHOR: &str = "Franquill0";
+
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct GoOptions {
+    pub depth: Option<u32>,
+    pub nodes: Option<u64>,
+    pub movetime: Option<u64>,
+    pub wtime: Option<u64>,
+    pub btime: Option<u64>,
+    pub winc: Option<u64>,
+    pub binc: Option<u64>,
+    pub movestogo: Option<u32>,
+    pub infinite: bool,
+}
+
+struct Search {
+    handle: JoinHandle<()>,
+    stop: Arc<AtomicBool>,
+}
+
+pub struct Uci<W: Write + Send + 'static> {
+    board: Board,
+    output: Arc<Mutex<W>>,
+    search: Option<Search>,
+    tt: Arc<Mutex<TranspositionTable>>,
+    threads: usize,
+}
+
+impl GoOptions {
+    pub fn parse(arguments: &[&str]) -> GoOptions {
+        let mut options = GoOptions::default();
+        let mut index = 0;
+        while index < arguments.len() {
+            let value = arguments.get(index + 1).and_then(|value| value.parse::<u64>().ok());
+            let time = arguments.get(index + 1)
+                .and_then(|value| value.parse::<i64>().ok())
+                .map(|value| value.max(0) as u64);
+            match arguments[index] {
+                "depth" => options.depth = value.map(|value| value.min(MAX_DEPTH as u64) as u32),
+                "nodes" => options.nodes = value,
+                "movetime" => options.movetime = time,
+                "wtime" => options.wtime = time,
+                "btime" => options.btime = time,
+                "winc" => options.winc = time,
+                "binc" => options.binc = time,
+                "movestogo" => options.movestogo = value.and_then(|value| u32::try_from(value).ok()),
+                "infinite" => {
+                    options.infinite = true;
+                    index += 1;
+                    continue;
+                },
+                _ => {
+                    index += 1;
+                    continue;
+                },
+            }
+            index += 2;
+        }
+        options
+    }
+}
+
+impl<W: Write + Send + 'static> Uci<W> {
+    pub fn new(output: W) -> Self {
+        Self {
+            board: Board::from_fen(INITIAL_FEN).unwrap(),
+            output: Arc::new(Mutex::new(output)),
+            search: None,
+            tt: Arc::new(Mutex::new(TranspositionTable::new(DEFAULT_HASH_MB))),
+            threads: 1,
+        }
+    }
+    pub fn run<R: BufRead>(&mut self, input: R) {
+        for line in input.lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(_) => break,
+            };
+            if !self.handle_command(&line) {
+                break;
+            }
+        }
+        self.stop_search();
+    }
+    pub fn get_board(&self) -> &Board {
+        &self.board
+    }
+    pub fn handle_command(&mut self, line: &str) -> bool {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let (command, arguments) = match words.split_first() {
+            Some((command, arguments)) => (*command, arguments),
+            None => return true,
+        };
+        match command {
+            "uci" => {
+                self.send(&format!("id name {}", ENGINE_NAME));
+                self.send(&format!("id author {}", ENGINE_AUTHOR));
+                self.send(&format!("option name Hash type spin default {} min 1 max {}", DEFAULT_HASH_MB, MAX_HASH_MB));
+                self.send(&format!("option name Threads type spin default 1 min 1 max {}", MAX_THREADS));
+                self.send("uciok");
+            },
+            "isready" => self.send("readyok"),
+            "ucinewgame" => {
+                self.stop_search();
+                self.board = Board::from_fen(INITIAL_FEN).unwrap();
+                self.tt.lock().unwrap().clear();
+            },
+            "setoption" => {
+                self.stop_search();
+                if let Err(error) = self.set_option(arguments) {
+                    self.send(&format!("info string {}", error));
+                }
+            },
+            "position" => {
+                self.stop_search();
+                if let Err(error) = self.set_position(arguments) {
+                    self.send(&format!("info string {}", error));
+                }
+            },
+            "go" => {
+                self.stop_search();
+                self.go(GoOptions::parse(arguments));
+            },
+            "stop" => self.stop_search(),
+            "quit" => return false,
+            _ => self.send(&format!("info string Unknown command: {}", line.trim())),
+        }
+        true
+    }
+    fn set_position(&mut self, arguments: &[&str]) -> Result<(), String> {
+        let moves_index = arguments.iter().position(|word| *word == "moves");
+        let (setup, moves) = match moves_index {
+            Some(index) => (&arguments[..index], &arguments[index + 1..]),
+            None => (arguments, &arguments[arguments.len()..]),
+        };
+        let mut board = match setup.split_first() {
+            Some((&"startpos", _)) => Board::from_fen(INITIAL_FEN).unwrap(),
+            Some((&"fen", fen)) => {
+                let mut fen = fen.join(" ");
+                if fen.split_whitespace().count() == 4 {
+                    fen.push_str(" 0 1");
+                }
+                Board::from_fen(&fen).map_err(|error| error.to_string())?
+            },
+            _ => return Err(String::from("Expected 'startpos' or 'fen'")),
+        };
+        for text in moves {
+            let mv = board.parse_move(text)?;
+            board.make_move(mv);
+        }
+        self.board = board;
+        Ok(())
+    }
+    fn set_option(&mut self, arguments: &[&str]) -> Result<(), String> {
+        let value_index = arguments.iter().position(|word| *word == "value");
+        let (name, value) = match (arguments.split_first(), value_index) {
+            (Some((&"name", _)), Some(index)) => (arguments[1..index].join(" "), arguments[index + 1..].join(" ")),
+            (Some((&"name", name)), None) => (name.join(" "), String::new()),
+            _ => return Err(String::from("Expected 'name'")),
+        };
+        match name.to_lowercase().as_str() {
+            "hash" => {
+                let megabytes = value.parse::<usize>()
+                    .ok()
+                    .filter(|megabytes| (1..=MAX_HASH_MB).contains(megabytes))
+                    .ok_or(format!("Invalid Hash value: '{}'", value))?;
+                self.tt.lock().unwrap().resize(megabytes);
+            },
+            "threads" => {
+                self.threads = value.parse::<usize>()
+                    .ok()
+                    .filter(|threads| (1..=MAX_THREADS).contains(threads))
+                    .ok_or(format!("Invalid Threads value: '{}'", value))?;
+            },
+            _ => return Err(format!("Unknown option: {}", name)),
+        }
+        Ok(())
+    }
+    fn go(&mut self, options: GoOptions) {
+        let board = self.board.clone();
+        let output = Arc::clone(&self.output);
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = Arc::clone(&stop);
+        let report_output = Arc::clone(&self.output);
+        let tt = Arc::clone(&self.tt);
+        let threads = self.threads;
+        let handle = thread::spawn(move || {
+            let tt = tt.lock().unwrap();
+            let best_move = think(&board, options, threads, &tt, &thread_stop, |result| {
+                let mut output = report_output.lock().unwrap();
+                let _ = writeln!(output, "{}", info_line(result));
+                let _ = output.flush();
+            });
+            let best_move = match best_move {
+                Some(mv) => mv.to_string(),
+                None => String::from("0000"),
+            };
+            let mut output = output.lock().unwrap();
+            let _ = writeln!(output, "bestmove {}", best_move);
+            let _ = output.flush();
+        });
+        self.search = Some(Search {handle, stop});
+    }
+    fn stop_search(&mut self) {
+        if let Some(search) = self.search.take() {
+            search.stop.store(true, Ordering::Relaxed);
+            let _ = search.handle.join();
+        }
+    }
+    fn send(&self, message: &str) {
+        let mut output = self.output.lock().unwrap();
+        let _ = writeln!(output, "{}", message);
+        let _ = output.flush();
+    }
+}
+
+impl GoOptions {
+    pub fn search_limits(&self, turn: Color) -> SearchLimits {
+        let mut limits = SearchLimits {depth: self.depth, nodes: self.nodes, ..SearchLimits::default()};
+        if self.infinite {
+            return limits;
+        }
+        let (time, increment) = match turn {
+            Color::White => (self.wtime, self.winc),
+            Color::Black => (self.btime, self.binc),
+        };
+        if let Some(movetime) = self.movetime {
+            limits.movetime = Some(Duration::from_millis(movetime));
+        } else if let Some(time) = time {
+            let increment = Duration::from_millis(increment.unwrap_or(0));
+            let time_limits = timeman::allocate(Duration::from_millis(time), increment, self.movestogo);
+            limits.soft_time = Some(time_limits.soft);
+            limits.movetime = Some(time_limits.hard);
+        }
+        limits
+    }
+}
+
+pub(crate) fn think<F: FnMut(&SearchResult)>(board: &Board, options: GoOptions, threads: usize, tt: &TranspositionTable, stop: &AtomicBool, report: F) -> Option<Move> {
+    let search_options = SearchOptions {threads, ..SearchOptions::default()};
+    let result = search::search_with_options(board, options.search_limits(board.get_turn()), search_options, tt, stop, report);
+    if options.infinite {
+        while !stop.load(Ordering::Relaxed) {
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+    result.best_move
+}
+
+fn info_line(result: &SearchResult) -> String {
+    let score = match result.score {
+        Score::Centipawns(centipawns) => format!("cp {}", centipawns),
+        Score::Mate(moves) => format!("mate {}", moves),
+    };
+    let pv: Vec<String> = result.pv.iter().map(|mv| mv.to_string()).collect();
+    format!("info depth {} score {} nodes {} time {} pv {}",
+        result.depth, score, result.nodes, result.time.as_millis(), pv.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone)]
+    struct SharedOutput(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedOutput {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn feed<W: Write + Send + 'static>(uci: &mut Uci<W>, commands: &str) {
+        for line in commands.lines() {
+            if !uci.handle_command(line) {
+                break;
+            }
+        }
+        if let Some(search) = uci.search.take() {
+            let _ = search.handle.join();
+        }
+    }
+
+    fn run_commands(commands: &str) -> (String, Board) {
+        let buffer = Arc::new(Mutex::new(Vec::new()));
+        let mut uci = Uci::new(SharedOutput(Arc::clone(&buffer)));
+        feed(&mut uci, commands);
+        let output = String::from_utf8(buffer.lock().unwrap().clone()).unwrap();
+        (output, uci.get_board().clone())
+    }
+
+    #[test]
+    fn handshake(){
+        let (output, _) = run_commands("uci\nisready\nquit\n");
+        assert_eq!(output, "id name Ajedrez\nid author Franquill0\noption name Hash type spin default 16 min 1 max 1024\noption name Threads type spin default 1 min 1 max 256\nuciok\nreadyok\n");
+    }
+
+    #[test]
+    fn set_positions(){
+        let (_, board) = run_commands("position startpos moves e2e4 e7e5 g1f3\n");
+        assert_eq!(board.to_fen(), "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2");
+        let (_, board) = run_commands("position fen 3k4/4P3/8/8/8/8/8/4K3 w - - 0 1 moves e7e8q\n");
+        assert_eq!(board.to_fen(), "3kQ3/8/8/8/8/8/8/4K3 b - - 0 1");
+        let (_, board) = run_commands("position fen 3k4/4P3/8/8/8/8/8/4K3 w - -\n");
+        assert_eq!(board.to_fen(), "3k4/4P3/8/8/8/8/8/4K3 w - - 0 1");
+        let (output, board) = run_commands("position startpos moves e2e5\n");
+        assert_eq!(output, "info string 'e2e5' is not a legal move!\n");
+        assert_eq!(board.to_fen(), INITIAL_FEN);
+    }
+
+    #[test]
+    fn go_and_stop(){
+        let (output, _) = run_commands("position startpos\ngo depth 1\nisready\n");
+        assert!(output.contains("info depth 1 score cp "));
+        assert!(output.contains("bestmove "));
+        assert!(output.contains("readyok\n"));
+        let (output, _) = run_commands("position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1\ngo depth 3\n");
+        assert!(output.starts_with("info depth 1 score mate 1 nodes "));
+        assert!(output.ends_with("pv a1a8\nbestmove a1a8\n"));
+        let (output, _) = run_commands("position startpos\ngo infinite\nstop\n");
+        assert!(output.contains("bestmove "));
+        let buffer = Arc::new(Mutex::new(Vec::new()));
+        Uci::new(SharedOutput(Arc::clone(&buffer))).run("position startpos\ngo infinite\n".as_bytes());
+        assert!(String::from_utf8(buffer.lock().unwrap().clone()).unwrap().contains("bestmove "));
+        let (output, _) = run_commands("position fen 7k/5QQ1/8/8/8/8/8/K7 b - - 0 1\ngo movetime 10\n");
+        assert_eq!(output, "bestmove 0000\n");
+    }
+
+    #[test]
+    fn hash_option(){
+        let buffer = Arc::new(Mutex::new(Vec::new()));
+        let mut uci = Uci::new(SharedOutput(Arc::clone(&buffer)));
+        let default_size = uci.tt.lock().unwrap().len();
+        uci.run("setoption name Hash value 32\n".as_bytes());
+        assert_eq!(uci.tt.lock().unwrap().len(), default_size * 2);
+        uci.run("setoption name Hash value 0\nsetoption name Foo value 1\n".as_bytes());
+        let output = String::from_utf8(buffer.lock().unwrap().clone()).unwrap();
+        assert_eq!(output, "info string Invalid Hash value: '0'\ninfo string Unknown option: Foo\n");
+    }
+
+    #[test]
+    fn threads_option(){
+        let buffer = Arc::new(Mutex::new(Vec::new()));
+        let mut uci = Uci::new(SharedOutput(Arc::clone(&buffer)));
+        feed(&mut uci, "setoption name Threads value 4\nsetoption name Threads value 0\n");
+        assert_eq!(uci.threads, 4);
+        feed(&mut uci, "position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1\ngo depth 3\n");
+        let output = String::from_utf8(buffer.lock().unwrap().clone()).unwrap();
+        assert!(output.starts_with("info string Invalid Threads value: '0'\ninfo depth 1 score mate 1 nodes "));
+        assert!(output.ends_with("bestmove a1a8\n"));
+    }
+
+    #[test]
+    fn parse_go_options(){
+        let options = GoOptions::parse(&["wtime", "1000", "btime", "2000", "winc", "10", "binc", "20", "movestogo", "5"]);
+        assert_eq!(options.wtime, Some(1000));
+        assert_eq!(options.btime, Some(2000));
+        assert_eq!(options.winc, Some(10));
+        assert_eq!(options.binc, Some(20));
+        assert_eq!(options.movestogo, Some(5));
+        let limits = options.search_limits(Color::Black);
+        let time_limits = timeman::allocate(Duration::from_millis(2000), Duration::from_millis(20), Some(5));
+        assert_eq!(limits.soft_time, Some(time_limits.soft));
+        assert_eq!(limits.movetime, Some(time_limits.hard));
+        let limits = options.search_limits(Color::White);
+        assert!(limits.soft_time < Some(time_limits.soft));
+        let limits = GoOptions::parse(&["movetime", "300", "wtime", "1000"]).search_limits(Color::White);
+        assert_eq!(limits.movetime, Some(Duration::from_millis(300)));
+        assert_eq!(limits.soft_time, None);
+        let options = GoOptions::parse(&["wtime", "-50", "btime", "1000", "winc", "-10"]);
+        assert_eq!(options.wtime, Some(0));
+        assert_eq!(options.winc, Some(0));
+        let limits = options.search_limits(Color::White);
+        assert_eq!(limits.soft_time, Some(Duration::ZERO));
+        assert_eq!(limits.movetime, Some(Duration::ZERO));
+        let options = GoOptions::parse(&["infinite"]);
+        assert!(options.infinite);
+        let options = GoOptions::parse(&["depth", "6", "movetime", "300"]);
+        assert_eq!(options.depth, Some(6));
+        assert_eq!(options.movetime, Some(300));
+        let options = GoOptions::parse(&["depth", "4294967297", "movestogo", "4294967297"]);
+        assert_eq!(options.depth, Some(MAX_DEPTH));
+        assert_eq!(options.movestogo, None);
+    }
+}
@@ -0,0 +1,468 @@
+use std::io::{BufRead, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::board::{Board, DrawReason, Outcome, INITIAL_FEN};
+use crate::pieces::Color;
+use crate::search::{Score, SearchResult, MAX_THREADS};
+use crate::tt::{TranspositionTable, DEFAULT_HASH_MB};
+use crate::uci::{self, GoOptions};
+
+const ENGINE_NAME: &str = "Ajedrez";
+
+struct Search {
+    handle: JoinHandle<()>,
+    stop: Arc<AtomicBool>,
+    play: Arc<AtomicBool>,
+}
+
+#[derive(Clone, Copy, Default)]
+struct TimeControl {
+    moves_per_session: Option<u32>,
+    increment: Option<u64>,
+    time_per_move: Option<Duration>,
+    depth: Option<u32>,
+    engine_time: Option<u64>,
+    opponent_time: Option<u64>,
+}
+
+pub struct Xboard<W: Write + Send + 'static> {
+    board: Arc<Mutex<Board>>,
+    output: Arc<Mutex<W>>,
+    search: Option<Search>,
+    engine_color: Color,
+    force: bool,
+    post: bool,
+    time_control: TimeControl,
+    tt: Arc<Mutex<TranspositionTable>>,
+    threads: usize,
+}
+
+impl TimeControl {
+    fn go_options(&self, engine_color: Color, fullmove_number: u32) -> GoOptions {
+        let mut options = GoOptions {depth: self.depth, ..GoOptions::default()};
+        if let Some(time) = self.time_per_move {
+            options.movetime = Some(time.as_millis() as u64);
+            return options;
+        }
+        let (engine_time, engine_increment) = (self.engine_time, self.increment);
+        let (opponent_time, opponent_increment) = (self.opponent_time, self.increment);
+        match engine_color {
+            Color::White => {
+                (options.wtime, options.winc) = (engine_time, engine_increment);
+                (options.btime, options.binc) = (opponent_time, opponent_increment);
+            },
+            Color::Black => {
+                (options.btime, options.binc) = (engine_time, engine_increment);
+                (options.wtime, options.winc) = (opponent_time, opponent_increment);
+            },
+        }
+        if let Some(moves) = self.moves_per_session.filter(|moves| *moves > 0) {
+            let played = (fullmove_number - 1) % moves;
+            options.movestogo = Some(moves - played);
+        }
+        options
+    }
+}
+
+impl<W: Write + Send + 'static> Xboard<W> {
+    pub fn new(output: W) -> Self {
+        Self {
+            board: Arc::new(Mutex::new(Board::from_fen(INITIAL_FEN).unwrap())),
+            output: Arc::new(Mutex::new(output)),
+            search: None,
+            engine_color: Color::Black,
+            force: false,
+            post: false,
+            time_control: TimeControl::default(),
+            tt: Arc::new(Mutex::new(TranspositionTable::new(DEFAULT_HASH_MB))),
+            threads: 1,
+        }
+    }
+    pub fn run<R: BufRead>(&mut self, input: R) {
+        for line in input.lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(_) => break,
+            };
+            if !self.handle_command(&line) {
+                self.abort_search();
+                return;
+            }
+        }
+        self.wait_search();
+    }
+    pub fn get_board(&self) -> Board {
+        self.board.lock().unwrap().clone()
+    }
+    pub fn handle_command(&mut self, line: &str) -> bool {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let (command, arguments) = match words.split_first() {
+            Some((command, arguments)) => (*command, arguments),
+            None => return true,
+        };
+        match command {
+            "xboard" | "accepted" | "rejected" | "random" | "hard" | "easy" | "computer" => {},
+            "post" => self.post = true,
+            "nopost" => self.post = false,
+            "protover" => self.send(&format!(
+                "feature myname=\"{}\" usermove=1 setboard=1 ping=1 playother=1 memory=1 smp=1 sigint=0 sigterm=0 colors=0 analyze=0 done=1",
+                ENGINE_NAME)),
+            "ping" => self.send(&format!("pong {}", arguments.join(" "))),
+            "new" => {
+                self.abort_search();
+                *self.board.lock().unwrap() = Board::from_fen(INITIAL_FEN).unwrap();
+                self.engine_color = Color::Black;
+                self.force = false;
+                self.time_control.time_per_move = None;
+                self.time_control.depth = None;
+                self.tt.lock().unwrap().clear();
+            },
+            "force" => {
+                self.abort_search();
+                self.force = true;
+            },
+            "go" => {
+                self.abort_search();
+                self.force = false;
+                self.engine_color = self.board.lock().unwrap().get_turn();
+                self.think();
+            },
+            "playother" => {
+                self.abort_search();
+                self.force = false;
+                self.engine_color = self.board.lock().unwrap().get_turn().opposite();
+            },
+            "?" => {
+                if let Some(search) = &self.search {
+                    search.stop.store(true, Ordering::Relaxed);
+                }
+                self.wait_search();
+            },
+            "usermove" => self.user_move(arguments.first().copied().unwrap_or("")),
+            "setboard" => {
+                self.abort_search();
+                match Board::from_fen(&arguments.join(" ")) {
+                    Ok(board) => *self.board.lock().unwrap() = board,
+                    Err(error) => self.send(&format!("tellusererror Illegal position: {}", error)),
+                }
+            },
+            "undo" => {
+                self.abort_search();
+                self.board.lock().unwrap().unmake_move();
+            },
+            "remove" => {
+                self.abort_search();
+                let mut board = self.board.lock().unwrap();
+                board.unmake_move();
+                board.unmake_move();
+            },
+            "level" => self.set_level(arguments),
+            "st" => match arguments.first().and_then(|value| value.parse::<f64>().ok()).and_then(|seconds| Duration::try_from_secs_f64(seconds).ok()) {
+                Some(time) => self.time_control.time_per_move = Some(time),
+                None => self.send(&format!("Error (wrong arguments): st {}", arguments.join(" "))),
+            },
+            "sd" => self.time_control.depth = arguments.first().and_then(|value| value.parse().ok()),
+            "time" => self.time_control.engine_time = Self::parse_centiseconds(arguments),
+            "otim" => self.time_control.opponent_time = Self::parse_centiseconds(arguments),
+            "memory" => match arguments.first().and_then(|value| value.parse::<usize>().ok()) {
+                Some(megabytes) => {
+                    self.abort_search();
+                    self.tt.lock().unwrap().resize(megabytes);
+                },
+                None => self.send(&format!("Error (wrong arguments): memory {}", arguments.join(" "))),
+            },
+            "cores" => match arguments.first().and_then(|value| value.parse::<usize>().ok()) {
+                Some(cores) => self.threads = cores.clamp(1, MAX_THREADS),
+                None => self.send(&format!("Error (wrong arguments): cores {}", arguments.join(" "))),
+            },
+            "result" => {
+                self.abort_search();
+                self.force = true;
+            },
+            "quit" => return false,
+            _ => self.send(&format!("Error (unknown command): {}", command)),
+        }
+        true
+    }
+    fn user_move(&mut self, text: &str) {
+        self.abort_search();
+        let parsed = self.board.lock().unwrap().parse_move(text);
+        match parsed {
+            Ok(mv) => {
+                let turn = {
+                    let mut board = self.board.lock().unwrap();
+                    board.make_move(mv);
+                    board.get_turn()
+                };
+                if !self.force && turn == self.engine_color {
+                    self.think();
+                }
+            },
+            Err(_) => self.send(&format!("Illegal move: {}", text)),
+        }
+    }
+    fn set_level(&mut self, arguments: &[&str]) {
+        if arguments.len() != 3 {
+            self.send(&format!("Error (wrong arguments): level {}", arguments.join(" ")));
+            return;
+        }
+        let base_seconds = match arguments[1].split_once(':') {
+            Some((minutes, seconds)) => minutes.parse::<u64>().ok()
+                .zip(seconds.parse::<u64>().ok())
+                .map(|(minutes, seconds)| minutes * 60 + seconds),
+            None => arguments[1].parse::<u64>().ok().map(|minutes| minutes * 60),
+        };
+        let moves_per_session = arguments[0].parse().ok();
+        let increment = arguments[2].parse::<f64>().ok();
+        match (moves_per_session, base_seconds, increment) {
+            (Some(moves), Some(seconds), Some(increment)) => {
+                self.time_control.moves_per_session = Some(moves);
+                self.time_control.engine_time = Some(seconds * 1000);
+                self.time_control.opponent_time = Some(seconds * 1000);
+                self.time_control.increment = Some((increment * 1000.0) as u64);
+                self.time_control.time_per_move = None;
+            },
+            _ => self.send(&format!("Error (wrong arguments): level {}", arguments.join(" "))),
+        }
+    }
+    fn parse_centiseconds(arguments: &[&str]) -> Option<u64> {
+        arguments.first()
+            .and_then(|value| value.parse::<u64>().ok())
+            .map(|centiseconds| centiseconds * 10)
+    }
+    fn think(&mut self) {
+        let board = Arc::clone(&self.board);
+        let output = Arc::clone(&self.output);
+        let stop = Arc::new(AtomicBool::new(false));
+        let play = Arc::new(AtomicBool::new(true));
+        let (thread_stop, thread_play) = (Arc::clone(&stop), Arc::clone(&play));
+        let position = self.board.lock().unwrap().clone();
+        if let Some(claim) = result_claim(&position) {
+            self.send(&format!("result {}", claim));
+            return;
+        }
+        let options = self.time_control.go_options(self.engine_color, position.get_fullmove_number());
+        let post = self.post;
+        let report_output = Arc::clone(&self.output);
+        let tt = Arc::clone(&self.tt);
+        let threads = self.threads;
+        let handle = thread::spawn(move || {
+            let tt = tt.lock().unwrap();
+            let best_move = uci::think(&position, options, threads, &tt, &thread_stop, |result| {
+                if post {
+                    let mut output = report_output.lock().unwrap();
+                    let _ = writeln!(output, "{}", thinking_line(result));
+                    let _ = output.flush();
+                }
+            });
+            if !thread_play.load(Ordering::Relaxed) {
+                return;
+            }
+            let mv = match best_move {
+                Some(mv) => mv,
+                None => return,
+            };
+            let mut board = board.lock().unwrap();
+            board.make_move(mv);
+            let mut output = output.lock().unwrap();
+            let _ = writeln!(output, "move {}", mv);
+            if let Some(claim) = result_claim(&board) {
+                let _ = writeln!(output, "result {}", claim);
+            }
+            let _ = output.flush();
+        });
+        self.search = Some(Search {handle, stop, play});
+    }
+    fn abort_search(&mut self) {
+        if let Some(search) = &self.search {
+            search.play.store(false, Ordering::Relaxed);
+            search.stop.store(true, Ordering::Relaxed);
+        }
+        self.wait_search();
+    }
+    fn wait_search(&mut self) {
+        if let Some(search) = self.search.take() {
+            let _ = search.handle.join();
+        }
+    }
+    fn send(&self, message: &str) {
+        let mut output = self.output.lock().unwrap();
+        let _ = writeln!(output, "{}", message);
+        let _ = output.flush();
+    }
+}
+
+fn result_claim(board: &Board) -> Option<String> {
+    let claim = match board.outcome() {
+        Outcome::Checkmate(Color::White) => "1-0 {White mates}",
+        Outcome::Checkmate(Color::Black) => "0-1 {Black mates}",
+        Outcome::Stalemate => "1/2-1/2 {Stalemate}",
+        Outcome::Draw(reason) => draw_claim(reason),
+        Outcome::Ongoing => draw_claim(board.claimable_draw()?),
+    };
+    Some(claim.to_string())
+}
+
+fn draw_claim(reason: DrawReason) -> &'static str {
+    match reason {
+        DrawReason::FiftyMoves | DrawReason::SeventyFiveMoves => "1/2-1/2 {Draw by fifty move rule}",
+        DrawReason::ThreefoldRepetition | DrawReason::FivefoldRepetition => "1/2-1/2 {Draw by repetition}",
+        DrawReason::InsufficientMaterial => "1/2-1/2 {Insufficient material}",
+    }
+}
+
+fn thinking_line(result: &SearchResult) -> String {
+    let score = match result.score {
+        Score::Centipawns(centipawns) => centipawns,
+        Score::Mate(moves) if moves > 0 => 100000 + moves,
+        Score::Mate(moves) => -100000 + moves,
+    };
+    let pv: Vec<String> = result.pv.iter().map(|mv| mv.to_string()).collect();
+    format!("{} {} {} {} {}", result.depth, score, result.time.as_millis() / 10, result.nodes, pv.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[derive(Clone)]
+    struct SharedOutput(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedOutput {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn run_commands(commands: &str) -> (String, Board) {
+        let buffer = Arc::new(Mutex::new(Vec::new()));
+        let mut xboard = Xboard::new(SharedOutput(Arc::clone(&buffer)));
+        xboard.run(commands.as_bytes());
+        let output = String::from_utf8(buffer.lock().unwrap().clone()).unwrap();
+        (output, xboard.get_board())
+    }
+
+    #[test]
+    fn handshake(){
+        let (output, _) = run_commands("xboard\nprotover 2\nping 7\n");
+        assert!(output.starts_with("feature myname=\"Ajedrez\" usermove=1 setboard=1"));
+        assert!(output.contains("done=1\n"));
+        assert!(output.ends_with("pong 7\n"));
+    }
+
+    #[test]
+    fn engine_replies_to_user_moves(){
+        let (output, board) = run_commands("new\nsd 2\nusermove e2e4\n");
+        assert!(output.starts_with("move "));
+        assert!(board.get_turn() == Color::White);
+        assert_eq!(board.get_fullmove_number(), 2);
+
+        let (output, board) = run_commands("new\nforce\nusermove e2e4\nusermove e7e5\n");
+        assert_eq!(output, "");
+        assert_eq!(board.to_fen(), "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2");
+
+        let (output, board) = run_commands("new\nsd 2\nforce\nusermove e2e4\ngo\n");
+        assert!(output.starts_with("move "));
+        assert_eq!(board.get_fullmove_number(), 2);
+    }
+
+    #[test]
+    fn engine_posts_thinking(){
+        let (output, _) = run_commands("force\nsetboard 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1\npost\nsd 3\ngo\n");
+        assert!(output.starts_with("1 100001 "));
+        assert!(output.ends_with(" a1a8\nmove a1a8\nresult 1-0 {White mates}\n"));
+    }
+
+    #[test]
+    fn ping_does_not_wait_for_search(){
+        let buffer = Arc::new(Mutex::new(Vec::new()));
+        let mut xboard = Xboard::new(SharedOutput(Arc::clone(&buffer)));
+        xboard.handle_command("new");
+        xboard.handle_command("st 60");
+        xboard.handle_command("go");
+        xboard.handle_command("ping 3");
+        assert_eq!(String::from_utf8(buffer.lock().unwrap().clone()).unwrap(), "pong 3\n");
+        xboard.handle_command("force");
+    }
+
+    #[test]
+    fn claims_finished_games(){
+        let (output, _) = run_commands("force\nsetboard 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1\nsd 2\ngo\n");
+        assert_eq!(output, "move a1a8\nresult 1-0 {White mates}\n");
+        let (output, _) = run_commands("force\nsetboard 7k/5Q2/6K1/8/8/8/8/8 b - - 0 1\ngo\n");
+        assert_eq!(output, "result 1/2-1/2 {Stalemate}\n");
+        let (output, _) = run_commands("force\nsetboard 8/8/8/8/8/5k2/4q3/7K b - - 0 1\nplayother\nusermove e2g2\n");
+        assert_eq!(output, "result 0-1 {Black mates}\n");
+        let (output, _) = run_commands("force\nsetboard 7k/8/8/8/8/8/8/6NK b - - 0 1\ngo\n");
+        assert_eq!(output, "result 1/2-1/2 {Insufficient material}\n");
+    }
+
+    #[test]
+    fn memory_resizes_hash(){
+        let mut xboard = Xboard::new(Vec::new());
+        xboard.handle_command("memory 1");
+        let small = xboard.tt.lock().unwrap().len();
+        xboard.handle_command("memory 2");
+        assert_eq!(xboard.tt.lock().unwrap().len(), small * 2);
+    }
+
+    #[test]
+    fn cores_sets_threads(){
+        let mut xboard = Xboard::new(Vec::new());
+        xboard.handle_command("cores 4");
+        assert_eq!(xboard.threads, 4);
+        xboard.handle_command("cores 0");
+        assert_eq!(xboard.threads, 1);
+    }
+
+    #[test]
+    fn illegal_and_unknown_commands(){
+        let (output, _) = run_commands("new\nusermove e2e5\nfoo\n");
+        assert_eq!(output, "Illegal move: e2e5\nError (unknown command): foo\n");
+    }
+
+    #[test]
+    fn setboard_undo_and_remove(){
+        let (_, board) = run_commands("force\nsetboard 4k3/8/8/8/8/8/8/4K2R w K - 0 1\nusermove e1g1\nusermove e8d7\nundo\n");
+        assert_eq!(board.to_fen(), "4k3/8/8/8/8/8/8/5RK1 b - - 1 1");
+        let (_, board) = run_commands("force\nusermove e2e4\nusermove e7e5\nremove\n");
+        assert_eq!(board.to_fen(), INITIAL_FEN);
+    }
+
+    #[test]
+    fn time_controls(){
+        let mut xboard = Xboard::new(Vec::new());
+        xboard.handle_command("level 40 5 0");
+        xboard.handle_command("time 30000");
+        xboard.handle_command("otim 25000");
+        let options = xboard.time_control.go_options(Color::Black, 1);
+        assert_eq!(options.btime, Some(300000));
+        assert_eq!(options.wtime, Some(250000));
+        assert_eq!(options.binc, Some(0));
+        assert_eq!(options.movestogo, Some(40));
+        let limits = options.search_limits(Color::Black);
+        assert!(limits.soft_time.is_some_and(|soft| soft < Duration::from_secs(300) / 40));
+        assert!(limits.movetime > limits.soft_time);
+        xboard.handle_command("level 0 2:30 1.5");
+        let options = xboard.time_control.go_options(Color::White, 12);
+        assert_eq!(options.wtime, Some(150000));
+        assert_eq!(options.winc, Some(1500));
+        assert_eq!(options.movestogo, None);
+        xboard.handle_command("st 3");
+        xboard.handle_command("sd 4");
+        let options = xboard.time_control.go_options(Color::White, 1);
+        assert_eq!(options.movetime, Some(3000));
+        xboard.handle_command("st 0.5");
+        assert_eq!(xboard.time_control.go_options(Color::White, 1).movetime, Some(500));
+        xboard.handle_command("st fast");
+        assert_eq!(xboard.time_control.go_options(Color::White, 1).movetime, Some(500));
+        assert_eq!(options.depth, Some(4));
+    }
+}